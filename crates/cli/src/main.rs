@@ -1,5 +1,6 @@
 use anyhow::{Context, Result};
 use clap::Parser;
+use parser::{ParseError, Parser as SqlParser, Span};
 use poubelle_sdk::{PoubelleClient, Row, Value};
 use prettytable::{Cell, Row as TableRow, Table};
 use rustyline::error::ReadlineError;
@@ -20,6 +21,12 @@ struct Args {
     /// Execute a single command and exit
     #[arg(short = 'e', long)]
     command: Option<String>,
+
+    /// Bind a positional parameter for --command (e.g. -p 1 -p hello binds
+    /// $1=1, $2='hello'). Repeat in order; NULL (case-insensitive) binds
+    /// NULL and anything else that doesn't parse as an integer binds text.
+    #[arg(short = 'p', long = "param")]
+    params: Vec<String>,
 }
 
 #[tokio::main]
@@ -36,7 +43,12 @@ async fn main() -> Result<()> {
 
     // If command is provided, execute it and exit
     if let Some(cmd) = args.command {
-        execute_command(&mut client, &cmd).await?;
+        let params: Vec<Value> = args.params.iter().map(|s| parse_param(s)).collect();
+        if params.is_empty() {
+            execute_command(&mut client, &cmd).await?;
+        } else {
+            execute_prepared_command(&mut client, &cmd, &params).await?;
+        }
         client.close().await?;
         return Ok(());
     }
@@ -139,6 +151,64 @@ async fn execute_command(client: &mut PoubelleClient, sql: &str) -> Result<()> {
     let start = std::time::Instant::now();
 
     match client.execute(sql).await {
+        Ok(rows) => {
+            let duration = start.elapsed();
+
+            if rows.is_empty() {
+                println!("OK ({}ms)", duration.as_millis());
+            } else {
+                print_table(&rows);
+                println!("\n{} row(s) ({}ms)", rows.len(), duration.as_millis());
+            }
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            if let Some(span) = locate_parse_error(sql) {
+                print_caret(sql, span);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Re-parse `sql` locally with the same lexer/parser the server runs,
+/// purely to recover the span of the token that made it fail — the wire
+/// protocol only sends back a formatted error message, not the span itself,
+/// so this is the only way `execute_command` has to point at where things
+/// went wrong. `None` means either `sql` parsed fine locally (the server's
+/// error came from somewhere else, e.g. a missing table) or parsing failed
+/// in a way with no associated span.
+fn locate_parse_error(sql: &str) -> Option<Span> {
+    match SqlParser::new(sql).parse() {
+        Ok(_) => None,
+        Err(ParseError::UnexpectedToken(_, span)) => Some(span),
+        Err(ParseError::ExpectedToken(_, span)) => Some(span),
+    }
+}
+
+/// Echo `sql` back with a caret under the byte offset where it failed to
+/// parse.
+fn print_caret(sql: &str, span: Span) {
+    println!("{}", sql);
+    let offset = span.start.min(sql.len());
+    let caret_column = sql[..offset].chars().count();
+    println!("{}^", " ".repeat(caret_column));
+}
+
+async fn execute_prepared_command(
+    client: &mut PoubelleClient,
+    sql: &str,
+    params: &[Value],
+) -> Result<()> {
+    let sql = sql.trim();
+    if sql.is_empty() {
+        return Ok(());
+    }
+
+    let start = std::time::Instant::now();
+
+    match client.execute_prepared(sql, params).await {
         Ok(rows) => {
             let duration = start.elapsed();
 
@@ -157,6 +227,20 @@ async fn execute_command(client: &mut PoubelleClient, sql: &str) -> Result<()> {
     Ok(())
 }
 
+/// Heuristically convert a `--param` command-line string into a bind value:
+/// `NULL` (case-insensitive) becomes `Value::Null`, anything that parses as
+/// an `i64` becomes `Value::Int`, and everything else is passed through as
+/// `Value::Text`.
+fn parse_param(raw: &str) -> Value {
+    if raw.eq_ignore_ascii_case("null") {
+        return Value::Null;
+    }
+    if let Ok(n) = raw.parse::<i64>() {
+        return Value::Int(n);
+    }
+    Value::Text(raw.to_string())
+}
+
 async fn handle_meta_command(client: &mut PoubelleClient, cmd: &str) -> Result<bool> {
     match cmd {
         "\\q" | "\\quit" => {
@@ -271,5 +355,10 @@ fn format_value(value: &Value) -> String {
         Value::Int(n) => n.to_string(),
         Value::Text(s) => s.clone(),
         Value::Null => "NULL".to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Timestamp(ts) => chrono::DateTime::<chrono::Utc>::from_timestamp_millis(*ts)
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_else(|| ts.to_string()),
     }
 }
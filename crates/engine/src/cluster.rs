@@ -0,0 +1,126 @@
+//! Static cluster map and query routing so an [`crate::Engine`] can own
+//! only a subset of tables and forward statements against the rest to
+//! whichever peer does.
+//!
+//! The map itself (which table lives on which node, and how to reach it)
+//! is loaded once from `POUBELLE_CLUSTER_CONFIG`; how a forwarded
+//! statement actually reaches that peer is kept behind the
+//! [`ClusterTransport`] trait, the same way `raft::RaftTransport`
+//! abstracts node-to-node RPCs — production code drives it over a real
+//! connection while tests can stub it out.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use storage::Row;
+use thiserror::Error;
+
+/// Identifies a node within a cluster config. Unlike `raft::NodeId` (a
+/// small integer agreed on out of band), cluster nodes are named in the
+/// config file itself, since the cluster map and the raft peer set are
+/// independent concepts that may not even share a node-naming scheme.
+pub type ClusterNodeId = String;
+
+#[derive(Error, Debug)]
+pub enum ClusterError {
+    #[error("failed to read cluster config {0}: {1}")]
+    Io(String, std::io::Error),
+    #[error("failed to parse cluster config {0}: {1}")]
+    Parse(String, serde_json::Error),
+    #[error("forwarding statement to node {0} failed: {1}")]
+    Transport(ClusterNodeId, String),
+}
+
+/// Where to reach a node over TCP.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NodeAddr {
+    pub host: String,
+    pub port: u16,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ClusterConfigFile {
+    /// This node's own id, so it can tell its own assignments apart from a
+    /// peer's.
+    #[serde(rename = "self")]
+    self_id: ClusterNodeId,
+    /// node id -> address.
+    nodes: HashMap<ClusterNodeId, NodeAddr>,
+    /// table -> owning node id.
+    tables: HashMap<String, ClusterNodeId>,
+    /// Credentials the internal client authenticates to peers with.
+    username: String,
+    password: String,
+}
+
+/// Which node owns which table, and how to reach each one — loaded once at
+/// startup from `POUBELLE_CLUSTER_CONFIG`. A node with no config behaves
+/// exactly as a standalone server: every table is local.
+#[derive(Debug, Clone)]
+pub struct ClusterMap {
+    self_id: ClusterNodeId,
+    nodes: HashMap<ClusterNodeId, NodeAddr>,
+    tables: HashMap<String, ClusterNodeId>,
+    username: String,
+    password: String,
+}
+
+impl ClusterMap {
+    /// Load the cluster map from `POUBELLE_CLUSTER_CONFIG`, or `None` if
+    /// the env var isn't set.
+    pub fn from_env() -> Result<Option<Self>, ClusterError> {
+        let Ok(path) = env::var("POUBELLE_CLUSTER_CONFIG") else {
+            return Ok(None);
+        };
+
+        let contents = fs::read_to_string(&path).map_err(|e| ClusterError::Io(path.clone(), e))?;
+        let config: ClusterConfigFile =
+            serde_json::from_str(&contents).map_err(|e| ClusterError::Parse(path.clone(), e))?;
+
+        Ok(Some(Self {
+            self_id: config.self_id,
+            nodes: config.nodes,
+            tables: config.tables,
+            username: config.username,
+            password: config.password,
+        }))
+    }
+
+    /// The peer that owns `table`, or `None` if it's local to this node —
+    /// including a table with no explicit assignment, so tables default to
+    /// staying wherever they were created.
+    pub fn owner_of(&self, table: &str) -> Option<(&ClusterNodeId, &NodeAddr)> {
+        let owner_id = self.tables.get(table)?;
+        if owner_id == &self.self_id {
+            return None;
+        }
+        self.nodes.get(owner_id).map(|addr| (owner_id, addr))
+    }
+
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+}
+
+/// How a forwarded statement actually reaches the owning peer. Kept as a
+/// trait so `Engine` doesn't need to know whether that's a real connection
+/// or a stub.
+#[async_trait]
+pub trait ClusterTransport: Send + Sync {
+    /// Run `sql` against `node` (reachable at `addr`) and return the rows
+    /// it streamed back, unchanged — the receiving node executes `sql` on
+    /// its own local storage and relays the result exactly as it would to
+    /// a directly-connected client.
+    async fn forward(
+        &self,
+        node: &ClusterNodeId,
+        addr: &NodeAddr,
+        sql: &str,
+    ) -> Result<Vec<Row>, ClusterError>;
+}
@@ -1,7 +1,11 @@
-use crate::executor::{Executor, ExecutorError, QueryResult};
-use parser::{ParseError, Parser};
+use crate::cluster::{ClusterMap, ClusterTransport};
+use crate::executor::{Executor, ExecutorError, QueryResult, SqlState};
+use crate::raft::{NodeId, RaftError, RaftNode};
+use parser::{BindError, Expr, ParseError, Parser, Statement};
+use std::sync::Arc;
 use storage::Storage;
 use thiserror::Error;
+use tokio::sync::Mutex;
 
 #[derive(Error, Debug)]
 pub enum EngineError {
@@ -9,28 +13,490 @@ pub enum EngineError {
     Parse(#[from] ParseError),
     #[error("Execution error: {0}")]
     Execution(#[from] ExecutorError),
+    #[error("Parameter binding error: {0}")]
+    Bind(#[from] BindError),
+    #[error("A transaction is already in progress")]
+    AlreadyInTransaction,
+    #[error("No transaction is in progress")]
+    NoActiveTransaction,
+    #[error("Unknown savepoint: {0}")]
+    UnknownSavepoint(String),
+    #[error("Not the leader; last known leader is {0:?}")]
+    NotLeader(Option<NodeId>),
+    #[error("Replication error: {0}")]
+    Replication(String),
 }
 
+impl EngineError {
+    /// The [`SqlState`] class this error belongs to, for clients that want
+    /// to branch on error category instead of matching on `{0}`'s text.
+    pub fn sqlstate(&self) -> SqlState {
+        match self {
+            EngineError::Parse(_) => SqlState::SyntaxErrorOrAccessRuleViolation,
+            EngineError::Execution(e) => e.sqlstate(),
+            EngineError::Bind(_) => SqlState::Other("42P02".to_string()),
+            EngineError::AlreadyInTransaction
+            | EngineError::NoActiveTransaction
+            | EngineError::UnknownSavepoint(_) => SqlState::Other("25000".to_string()),
+            EngineError::NotLeader(_) | EngineError::Replication(_) => {
+                SqlState::Other("58000".to_string())
+            }
+        }
+    }
+}
+
+impl From<RaftError> for EngineError {
+    fn from(e: RaftError) -> Self {
+        match e {
+            RaftError::NotLeader(hint) => EngineError::NotLeader(hint),
+            other => EngineError::Replication(other.to_string()),
+        }
+    }
+}
+
+/// Tracks the statements queued inside an open `BEGIN ... COMMIT` block and
+/// the savepoints marked within it.
+///
+/// Writes issued while a transaction is open are buffered rather than
+/// applied to storage; `COMMIT` replays them in order, and `ROLLBACK`
+/// discards them. `SELECT`/`SELECT ...` expression statements are never
+/// buffered — see `Engine::execute_statement` — since a read has nothing
+/// to undo and a client needs its rows back immediately, not at commit
+/// time. This means a read inside an open transaction sees only what's
+/// already committed, not the transaction's own pending writes (no
+/// read-your-own-writes), which is weaker than proper MVCC isolation but
+/// is enough to make BEGIN/COMMIT/ROLLBACK/SAVEPOINT behave correctly from
+/// the outside.
+///
+/// Each pending write keeps the raw SQL text it was issued with alongside
+/// its parsed `Statement` (`None` for one that arrived pre-parsed, e.g. via
+/// `execute_prepared`), so `COMMIT` can still forward it to the owning peer
+/// if it targets a remote table — forwarding a buffered write at the moment
+/// it's issued, rather than at replay time, would run it on the peer
+/// immediately and make it impossible for a later `ROLLBACK` to undo.
+struct Transaction {
+    pending: Vec<(Statement, Option<String>)>,
+    savepoints: Vec<(String, usize)>,
+}
+
+impl Transaction {
+    fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            savepoints: Vec::new(),
+        }
+    }
+}
+
+/// A configured cluster map plus the transport that forwards statements to
+/// whichever peer owns the table they target. Bundled together since
+/// `with_cluster` is always given both at once.
+struct ClusterRouting {
+    map: ClusterMap,
+    transport: Arc<dyn ClusterTransport>,
+}
+
+/// Cheaply [`Clone`]able handle onto a database's storage, transaction
+/// state, and (optional) raft replication — safe to hand to every
+/// connection directly instead of wrapping the whole `Engine` in one
+/// external `Mutex`. `storage` is already internally concurrent (see
+/// `storage::Storage`'s own per-table `DashMap`), so a read on table A and
+/// a write to table B never wait on each other here; `transaction` and
+/// `raft` keep their own narrow locks, held only for the bookkeeping they
+/// guard rather than for the lifetime of a whole query.
+#[derive(Clone)]
 pub struct Engine {
-    storage: Storage,
+    storage: Arc<Storage>,
+    transaction: Arc<Mutex<Option<Transaction>>>,
+    raft: Arc<Mutex<Option<RaftNode>>>,
+    cluster: Option<Arc<ClusterRouting>>,
 }
 
 impl Engine {
     pub fn new(storage: Storage) -> Self {
-        Self { storage }
+        Self {
+            storage: Arc::new(storage),
+            transaction: Arc::new(Mutex::new(None)),
+            raft: Arc::new(Mutex::new(None)),
+            cluster: None,
+        }
+    }
+
+    /// Replicate writes through `raft` instead of applying them to
+    /// `storage` directly — see [`crate::raft`] for what that buys:
+    /// durability and availability across machines rather than one local
+    /// directory. Reads are only served while this node is the leader.
+    pub fn with_raft(mut self, raft: RaftNode) -> Self {
+        self.raft = Arc::new(Mutex::new(Some(raft)));
+        self
+    }
+
+    /// Route statements against a table `map` assigns to another node
+    /// through `transport` instead of always executing locally — see
+    /// [`crate::cluster`] for how ownership is decided. Only statements
+    /// with a table name (`SELECT`/`INSERT`/`CREATE`/`DROP`) are ever
+    /// eligible for forwarding; transaction control always runs locally.
+    pub fn with_cluster(mut self, map: ClusterMap, transport: Arc<dyn ClusterTransport>) -> Self {
+        self.cluster = Some(Arc::new(ClusterRouting { map, transport }));
+        self
+    }
+
+    /// The node this engine currently believes is the cluster leader, or
+    /// `None` if replication isn't configured or no leader is known yet.
+    pub async fn raft_leader_hint(&self) -> Option<NodeId> {
+        self.raft
+            .lock()
+            .await
+            .as_ref()
+            .and_then(RaftNode::leader_hint)
+    }
+
+    /// Add a node to the replicated cluster, if replication is configured.
+    pub async fn add_cluster_member(&self, peer: NodeId) {
+        if let Some(raft) = self.raft.lock().await.as_mut() {
+            raft.add_member(peer);
+        }
+    }
+
+    /// Remove a node from the replicated cluster, if replication is
+    /// configured.
+    pub async fn remove_cluster_member(&self, peer: NodeId) {
+        if let Some(raft) = self.raft.lock().await.as_mut() {
+            raft.remove_member(peer);
+        }
+    }
+
+    pub async fn execute_query(&self, query: &str) -> Result<QueryResult, EngineError> {
+        let mut parser = Parser::new(query);
+        let statement = parser.parse()?;
+        self.execute_statement(statement, Some(query)).await
     }
 
-    pub fn execute_query(&mut self, query: &str) -> Result<QueryResult, EngineError> {
+    /// Parse `query` as a `;`-separated batch of statements and execute
+    /// each in order under a single call, so a client doing bulk writes
+    /// pays one round trip instead of one per statement. A later
+    /// statement's error doesn't stop earlier or later statements from
+    /// running; each gets its own slot in the result.
+    pub async fn execute_batch(&self, query: &str) -> Vec<Result<QueryResult, EngineError>> {
+        let mut parser = Parser::new(query);
+        let mut statements = match parser.parse_batch() {
+            Ok(statements) => statements,
+            Err(e) => return vec![Err(EngineError::Parse(e))],
+        };
+
+        // Forwarding ships the client's original SQL text, not a
+        // re-rendered statement, so only a single-statement batch (one
+        // query per round trip, the common case) is eligible for routing —
+        // a `;`-separated batch has no per-statement substring to hand to
+        // the owning peer once it's been parsed.
+        if statements.len() == 1 {
+            let statement = statements.remove(0);
+            return vec![self.execute_statement(statement, Some(query)).await];
+        }
+
+        let mut results = Vec::with_capacity(statements.len());
+        for statement in statements {
+            results.push(self.execute_statement(statement, None).await);
+        }
+        results
+    }
+
+    /// Parse `query` and bind `params` into its positional placeholders
+    /// (`$1`, `$2`, ...) before executing it.
+    ///
+    /// Always runs locally (or through raft) rather than being forwarded:
+    /// forwarding ships raw SQL text to the owning peer, and by the time a
+    /// statement reaches here its placeholders are already substituted, so
+    /// there's no template left to re-derive the original literal text
+    /// from for a clean forward.
+    pub async fn execute_prepared(
+        &self,
+        query: &str,
+        params: &[storage::Value],
+    ) -> Result<QueryResult, EngineError> {
         let mut parser = Parser::new(query);
         let statement = parser.parse()?;
+        let params: Vec<Expr> = params.iter().map(value_to_expr).collect();
+        let statement = parser::bind_params(statement, &params)?;
+        self.execute_statement(statement, None).await
+    }
+
+    /// If `statement` targets a table a configured cluster assigns to
+    /// another node, forward `sql` there and return its result; `None`
+    /// means this node should execute the statement itself (no cluster is
+    /// configured, the statement has no table, the table is local, or there
+    /// is no raw SQL text to forward at all — see `execute_statement`'s
+    /// `sql` parameter).
+    ///
+    /// A forwarded read always comes back as `QueryResult::Rows`, even when
+    /// it matches nothing: collapsing an empty result to `Success` would
+    /// make a legitimate empty `SELECT` indistinguishable from a write
+    /// acknowledgement to any caller branching on the `QueryResult` variant.
+    /// A forwarded write reports `Success` when the peer sends back no rows,
+    /// and otherwise returns those rows (as a local versioned insert does).
+    async fn try_forward(
+        &self,
+        statement: &Statement,
+        sql: Option<&str>,
+    ) -> Result<Option<QueryResult>, EngineError> {
+        let Some(cluster) = &self.cluster else {
+            return Ok(None);
+        };
+        let Some(sql) = sql else {
+            return Ok(None);
+        };
+        let Some(table) = statement_table(statement) else {
+            return Ok(None);
+        };
+        let Some((node, addr)) = cluster.map.owner_of(table) else {
+            return Ok(None);
+        };
 
-        let mut executor = Executor::new(&mut self.storage);
-        let result = executor.execute(statement)?;
+        let rows = cluster
+            .transport
+            .forward(node, addr, sql)
+            .await
+            .map_err(|e| EngineError::Replication(e.to_string()))?;
 
-        Ok(result)
+        let is_read = matches!(statement, Statement::Select(_) | Statement::SelectExpr(_));
+        Ok(Some(if is_read {
+            QueryResult::Rows(rows)
+        } else if rows.is_empty() {
+            QueryResult::Success("OK".to_string())
+        } else {
+            QueryResult::Rows(rows)
+        }))
+    }
+
+    async fn execute_statement(
+        &self,
+        statement: Statement,
+        sql: Option<&str>,
+    ) -> Result<QueryResult, EngineError> {
+        match statement {
+            Statement::Begin => self.begin().await,
+            Statement::Commit => self.commit().await,
+            Statement::Rollback(savepoint) => self.rollback(savepoint).await,
+            Statement::Savepoint(name) => self.savepoint(name).await,
+            Statement::ReleaseSavepoint(name) => self.release_savepoint(name).await,
+            other => {
+                // Reads have nothing to undo, so unlike a write there's no
+                // reason to defer one until COMMIT — queuing it would mean
+                // `BEGIN; SELECT ...; COMMIT;` throws its rows away instead
+                // of returning them. Run it immediately against whatever is
+                // already committed.
+                let is_read = matches!(other, Statement::Select(_) | Statement::SelectExpr(_));
+
+                {
+                    let mut tx_guard = self.transaction.lock().await;
+                    if let Some(tx) = tx_guard.as_mut() {
+                        if is_read {
+                            drop(tx_guard);
+                            if let Some(result) = self.try_forward(&other, sql).await? {
+                                return Ok(result);
+                            }
+                            if let Some(raft) = self.raft.lock().await.as_ref() {
+                                if !raft.is_leader() {
+                                    return Err(EngineError::NotLeader(raft.leader_hint()));
+                                }
+                            }
+                            let mut executor = Executor::new(&self.storage);
+                            return Ok(executor.execute(other).await?);
+                        }
+
+                        // Buffer the raw SQL alongside the parsed statement
+                        // rather than forwarding it now: forwarding a write
+                        // to a remote-owned table at queue time would run it
+                        // on the peer immediately, and a later `ROLLBACK`
+                        // would have no way to undo it. `COMMIT` forwards it
+                        // (see below) when the statement actually replays.
+                        tx.pending.push((other, sql.map(str::to_string)));
+                        return Ok(QueryResult::Success(
+                            "Statement queued in transaction".to_string(),
+                        ));
+                    }
+                }
+
+                if let Some(result) = self.try_forward(&other, sql).await? {
+                    return Ok(result);
+                }
+
+                let raft_configured = self.raft.lock().await.is_some();
+                if raft_configured {
+                    let command = Executor::new(&self.storage).resolve_write(&other)?;
+
+                    if let Some(command) = command {
+                        let mut raft_guard = self.raft.lock().await;
+                        let raft = raft_guard.as_mut().unwrap();
+                        raft.propose(command, &self.storage).await?;
+                        return Ok(QueryResult::Success("OK".to_string()));
+                    }
+
+                    let raft_guard = self.raft.lock().await;
+                    let raft = raft_guard.as_ref().unwrap();
+                    if !raft.is_leader() {
+                        return Err(EngineError::NotLeader(raft.leader_hint()));
+                    }
+                }
+
+                let mut executor = Executor::new(&self.storage);
+                Ok(executor.execute(other).await?)
+            }
+        }
+    }
+
+    async fn begin(&self) -> Result<QueryResult, EngineError> {
+        let mut tx_guard = self.transaction.lock().await;
+        if tx_guard.is_some() {
+            return Err(EngineError::AlreadyInTransaction);
+        }
+        *tx_guard = Some(Transaction::new());
+        Ok(QueryResult::Success("BEGIN".to_string()))
+    }
+
+    async fn commit(&self) -> Result<QueryResult, EngineError> {
+        let tx = self
+            .transaction
+            .lock()
+            .await
+            .take()
+            .ok_or(EngineError::NoActiveTransaction)?;
+
+        for (statement, sql) in tx.pending {
+            if self
+                .try_forward(&statement, sql.as_deref())
+                .await?
+                .is_some()
+            {
+                continue;
+            }
+            let command = if self.raft.lock().await.is_some() {
+                Executor::new(&self.storage).resolve_write(&statement)?
+            } else {
+                None
+            };
+            if let Some(command) = command {
+                let mut raft_guard = self.raft.lock().await;
+                raft_guard.as_mut().unwrap().propose(command, &self.storage).await?;
+                continue;
+            }
+            let mut executor = Executor::new(&self.storage);
+            executor.execute(statement).await?;
+        }
+
+        Ok(QueryResult::Success("COMMIT".to_string()))
+    }
+
+    async fn rollback(&self, savepoint: Option<String>) -> Result<QueryResult, EngineError> {
+        let mut tx_guard = self.transaction.lock().await;
+        let tx = tx_guard.as_mut().ok_or(EngineError::NoActiveTransaction)?;
+
+        match savepoint {
+            None => {
+                *tx_guard = None;
+                Ok(QueryResult::Success("ROLLBACK".to_string()))
+            }
+            Some(name) => {
+                let pos = tx
+                    .savepoints
+                    .iter()
+                    .rposition(|(sp_name, _)| sp_name == &name)
+                    .ok_or_else(|| EngineError::UnknownSavepoint(name.clone()))?;
+                let mark = tx.savepoints[pos].1;
+
+                tx.pending.truncate(mark);
+                // Every savepoint marked after `name` (including `name`
+                // itself) now points at or past the truncated `pending`,
+                // since whatever statements it was meant to rewind to are
+                // gone. Drop all of them, not just `name`, so a later
+                // `ROLLBACK TO` one of them reports "savepoint does not
+                // exist" instead of silently truncating to a stale mark.
+                tx.savepoints.truncate(pos);
+                Ok(QueryResult::Success(format!(
+                    "ROLLBACK TO SAVEPOINT {}",
+                    name
+                )))
+            }
+        }
+    }
+
+    async fn savepoint(&self, name: String) -> Result<QueryResult, EngineError> {
+        let mut tx_guard = self.transaction.lock().await;
+        let tx = tx_guard.as_mut().ok_or(EngineError::NoActiveTransaction)?;
+
+        let mark = tx.pending.len();
+        tx.savepoints.retain(|(sp_name, _)| sp_name != &name);
+        tx.savepoints.push((name.clone(), mark));
+        Ok(QueryResult::Success(format!("SAVEPOINT {}", name)))
+    }
+
+    async fn release_savepoint(&self, name: String) -> Result<QueryResult, EngineError> {
+        let mut tx_guard = self.transaction.lock().await;
+        let tx = tx_guard.as_mut().ok_or(EngineError::NoActiveTransaction)?;
+
+        let before = tx.savepoints.len();
+        tx.savepoints.retain(|(sp_name, _)| sp_name != &name);
+        if tx.savepoints.len() == before {
+            return Err(EngineError::UnknownSavepoint(name));
+        }
+        Ok(QueryResult::Success(format!("RELEASE SAVEPOINT {}", name)))
     }
 
     pub fn list_tables(&self) -> Vec<String> {
         self.storage.list_tables()
     }
+
+    /// Subscribe to rows inserted into `table` from here on — see
+    /// [`storage::Storage::subscribe`].
+    pub fn listen(&self, table: &str) -> tokio::sync::broadcast::Receiver<storage::Row> {
+        self.storage.subscribe(table)
+    }
+
+    /// Load the persisted `username -> password hash` table — see
+    /// [`storage::Storage::load_auth_users`].
+    pub async fn load_auth_users(
+        &self,
+    ) -> Result<std::collections::HashMap<String, String>, storage::StorageError> {
+        self.storage.load_auth_users().await
+    }
+
+    /// Persist the `username -> password hash` table — see
+    /// [`storage::Storage::save_auth_users`].
+    pub async fn save_auth_users(
+        &self,
+        users: &std::collections::HashMap<String, String>,
+    ) -> Result<(), storage::StorageError> {
+        self.storage.save_auth_users(users).await
+    }
+}
+
+/// Turn a bind parameter's storage-level value into the expression the
+/// parser would have produced had it appeared as a literal in the query.
+fn value_to_expr(value: &storage::Value) -> Expr {
+    match value {
+        storage::Value::Int(n) => Expr::Int(*n),
+        storage::Value::Text(s) => Expr::Text(s.clone()),
+        storage::Value::Float(f) => Expr::Float(*f),
+        storage::Value::Bool(b) => Expr::Bool(*b),
+        storage::Value::Timestamp(ts) => Expr::Int(*ts),
+        storage::Value::Null => Expr::Null,
+    }
+}
+
+/// The table `statement` reads or writes, if any — `None` for statements
+/// with no single table to route on (transaction control, `SELECT` of a
+/// bare expression).
+fn statement_table(statement: &Statement) -> Option<&str> {
+    match statement {
+        Statement::Select(query) => Some(&query.table),
+        Statement::Insert(insert) => Some(&insert.table),
+        Statement::Create(create) => Some(&create.name),
+        Statement::Drop(drop) => Some(&drop.name),
+        Statement::SelectExpr(_)
+        | Statement::Begin
+        | Statement::Commit
+        | Statement::Rollback(_)
+        | Statement::Savepoint(_)
+        | Statement::ReleaseSavepoint(_) => None,
+    }
 }
@@ -1,6 +1,11 @@
-use parser::{ArithmeticOp, CompareOp, Expr, OutputFormat, Statement, WhereClause};
+use crate::raft::Command;
+use chrono::{DateTime, Utc};
+use parser::{
+    AggregateFunc, ArithmeticOp, CompareOp, CreateTable, Expr, InsertStatement, OutputFormat,
+    SelectQuery, SortDir, Statement, WhereClause, WhereExpr,
+};
 use std::collections::HashMap;
-use storage::{ColumnType, Row, Storage, StorageError, Value};
+use storage::{ColumnType, Row, Storage, StorageError, Value, VersionContext};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -15,6 +20,76 @@ pub enum ExecutorError {
     DivisionByZero,
     #[error("Invalid operation: cannot perform arithmetic on non-integer values")]
     InvalidArithmetic,
+    #[error("Unbound parameter: ${0}")]
+    UnboundParameter(usize),
+    #[error("Invalid timestamp literal for column {0}")]
+    InvalidTimestamp(String),
+}
+
+impl ExecutorError {
+    /// The [`SqlState`] class this error belongs to, for clients that want
+    /// to branch on error category instead of matching on `{0}`'s text.
+    pub fn sqlstate(&self) -> SqlState {
+        match self {
+            ExecutorError::Storage(_) => SqlState::Other("XX000".to_string()),
+            ExecutorError::TypeMismatch(_) => SqlState::DatatypeMismatch,
+            ExecutorError::ColumnCountMismatch => SqlState::SyntaxErrorOrAccessRuleViolation,
+            ExecutorError::DivisionByZero => SqlState::DivisionByZero,
+            ExecutorError::InvalidArithmetic => SqlState::DatatypeMismatch,
+            ExecutorError::UnboundParameter(_) => SqlState::Other("42P02".to_string()),
+            ExecutorError::InvalidTimestamp(_) => SqlState::DatatypeMismatch,
+        }
+    }
+}
+
+/// A SQLSTATE-style error class, modeled on PostgreSQL's five-character
+/// error codes (see the "PostgreSQL Error Codes" appendix of its docs) so
+/// clients can match on a stable class rather than parsing an error
+/// message's text.
+///
+/// [`Self::code`] and [`Self::from_code`] round-trip through the string
+/// that actually travels over the wire; `Other` carries any code this enum
+/// doesn't name a variant for, including ones from error classes outside
+/// the executor (parse, bind, transaction, replication).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlState {
+    /// `22012` — division by zero.
+    DivisionByZero,
+    /// `42703` — reference to a column that doesn't exist.
+    UndefinedColumn,
+    /// `42804` — a value's type doesn't match what was expected.
+    DatatypeMismatch,
+    /// `42601` — malformed statement, including a column-count mismatch.
+    SyntaxErrorOrAccessRuleViolation,
+    Other(String),
+}
+
+static SQLSTATE_CODES: phf::Map<&'static str, SqlState> = phf::phf_map! {
+    "22012" => SqlState::DivisionByZero,
+    "42703" => SqlState::UndefinedColumn,
+    "42804" => SqlState::DatatypeMismatch,
+    "42601" => SqlState::SyntaxErrorOrAccessRuleViolation,
+};
+
+impl SqlState {
+    pub fn code(&self) -> &str {
+        match self {
+            SqlState::DivisionByZero => "22012",
+            SqlState::UndefinedColumn => "42703",
+            SqlState::DatatypeMismatch => "42804",
+            SqlState::SyntaxErrorOrAccessRuleViolation => "42601",
+            SqlState::Other(code) => code,
+        }
+    }
+
+    /// Look up the class for a code read off the wire, falling back to
+    /// `Other` for any code this enum doesn't name a variant for.
+    pub fn from_code(code: &str) -> SqlState {
+        SQLSTATE_CODES
+            .get(code)
+            .cloned()
+            .unwrap_or_else(|| SqlState::Other(code.to_string()))
+    }
 }
 
 #[derive(Debug)]
@@ -25,18 +100,18 @@ pub enum QueryResult {
 }
 
 pub struct Executor<'a> {
-    storage: &'a mut Storage,
+    storage: &'a Storage,
 }
 
 impl<'a> Executor<'a> {
-    pub fn new(storage: &'a mut Storage) -> Self {
+    pub fn new(storage: &'a Storage) -> Self {
         Self { storage }
     }
 
-    pub fn execute(&mut self, stmt: Statement) -> Result<QueryResult, ExecutorError> {
+    pub async fn execute(&mut self, stmt: Statement) -> Result<QueryResult, ExecutorError> {
         match stmt {
             Statement::Drop(drop) => {
-                self.storage.drop_table(&drop.name)?;
+                self.storage.drop_table(&drop.name).await?;
                 Ok(QueryResult::Success(format!("Table {} dropped", drop.name)))
             }
             Statement::SelectExpr(select_expr) => {
@@ -55,35 +130,37 @@ impl<'a> Executor<'a> {
                     OutputFormat::Json => {
                         let mut map = serde_json::Map::new();
                         for (key, value) in &row.data {
-                            let json_val = match value {
-                                Value::Int(i) => serde_json::Value::Number((*i).into()),
-                                Value::Text(s) => serde_json::Value::String(s.clone()),
-                                Value::Null => serde_json::Value::Null,
-                            };
-                            map.insert(key.clone(), json_val);
+                            map.insert(key.clone(), value_to_json(value));
                         }
-                        let json_string = serde_json::to_string_pretty(&[serde_json::Value::Object(map)])
-                            .unwrap_or_else(|_| "[]".to_string());
+                        let json_string =
+                            serde_json::to_string_pretty(&[serde_json::Value::Object(map)])
+                                .unwrap_or_else(|_| "[]".to_string());
                         Ok(QueryResult::RowsJson(json_string))
                     }
                     OutputFormat::Debug => Ok(QueryResult::Rows(vec![row])),
                 }
             }
             Statement::Select(select) => {
-                let mut rows = self.storage.scan_table(&select.table)?;
+                let mut rows = self.storage.scan_table(&select.table).await?;
 
                 if let Some(where_clause) = &select.where_clause {
-                    rows = rows
-                        .into_iter()
-                        .filter(|row| self.evaluate_where(row, where_clause))
-                        .collect();
+                    let mut filtered = Vec::with_capacity(rows.len());
+                    for row in rows {
+                        if self.evaluate_where(&row, where_clause)? {
+                            filtered.push(row);
+                        }
+                    }
+                    rows = filtered;
                 }
 
-                if let Some(limit) = select.limit {
-                    rows.truncate(limit);
-                }
+                let has_aggregates = select
+                    .projection
+                    .iter()
+                    .any(|item| matches!(item.expr, Expr::Aggregate { .. }));
 
-                let result_rows = if select.columns.contains(&"*".to_string()) {
+                let mut result_rows = if has_aggregates || !select.group_by.is_empty() {
+                    self.evaluate_grouped(&select, rows)?
+                } else if select.columns.contains(&"*".to_string()) {
                     rows
                 } else {
                     rows.into_iter()
@@ -99,6 +176,18 @@ impl<'a> Executor<'a> {
                         .collect()
                 };
 
+                if !select.order_by.is_empty() {
+                    sort_rows(&mut result_rows, &select.order_by);
+                }
+
+                if select.distinct {
+                    dedup_rows(&mut result_rows);
+                }
+
+                if let Some(limit) = select.limit {
+                    result_rows.truncate(limit);
+                }
+
                 match select.format {
                     OutputFormat::Json => {
                         let json_rows: Vec<serde_json::Value> = result_rows
@@ -106,12 +195,7 @@ impl<'a> Executor<'a> {
                             .map(|row| {
                                 let mut map = serde_json::Map::new();
                                 for (key, value) in &row.data {
-                                    let json_val = match value {
-                                        Value::Int(i) => serde_json::Value::Number((*i).into()),
-                                        Value::Text(s) => serde_json::Value::String(s.clone()),
-                                        Value::Null => serde_json::Value::Null,
-                                    };
-                                    map.insert(key.clone(), json_val);
+                                    map.insert(key.clone(), value_to_json(value));
                                 }
                                 serde_json::Value::Object(map)
                             })
@@ -129,92 +213,182 @@ impl<'a> Executor<'a> {
                     return Err(ExecutorError::ColumnCountMismatch);
                 }
 
-                let meta = self
-                    .storage
-                    .get_table_meta(&insert.table)
-                    .ok_or_else(|| StorageError::TableNotFound(insert.table.clone()))?;
+                let row = self.resolve_insert(&insert)?;
 
-                let mut row = Row::new();
-                for (col_name, expr) in insert.columns.iter().zip(insert.values.iter()) {
-                    let col_type = meta
-                        .columns
-                        .get(col_name)
-                        .ok_or_else(|| ExecutorError::TypeMismatch(col_name.clone()))?;
-
-                    let value = match (expr, col_type) {
-                        (Expr::Int(n), ColumnType::Int) => Value::Int(*n),
-                        (Expr::Text(s), ColumnType::Text) => Value::Text(s.clone()),
-                        (Expr::Null, _) => Value::Null,
-                        _ => return Err(ExecutorError::TypeMismatch(col_name.clone())),
-                    };
-
-                    row.insert(col_name.clone(), value);
+                if let Some(versioned) = &insert.versioned {
+                    // A blind write: the client has no prior context to
+                    // present, so every existing sibling is concurrent with
+                    // this one and survives alongside it.
+                    self.storage
+                        .insert_versioned_row(
+                            &insert.table,
+                            &versioned.key,
+                            &versioned.writer,
+                            row,
+                            &VersionContext::new(),
+                        )
+                        .await?;
+
+                    let siblings = self
+                        .storage
+                        .read_versioned_row(&insert.table, &versioned.key)?
+                        .map(|entry| entry.siblings.into_iter().map(|s| s.row).collect())
+                        .unwrap_or_default();
+                    return Ok(QueryResult::Rows(siblings));
                 }
 
-                self.storage.insert_row(&insert.table, row)?;
+                self.storage.insert_row(&insert.table, row).await?;
                 Ok(QueryResult::Success("Row inserted".to_string()))
             }
             Statement::Create(create) => {
-                let mut columns = HashMap::new();
-                for col in create.columns {
-                    let col_type = match col.column_type.as_str() {
-                        "INT" => ColumnType::Int,
-                        "TEXT" => ColumnType::Text,
-                        _ => return Err(ExecutorError::TypeMismatch(col.name)),
-                    };
-                    columns.insert(col.name, col_type);
-                }
-
-                self.storage.create_table(create.name.clone(), columns)?;
+                let columns = self.resolve_create(&create)?;
+                self.storage
+                    .create_table(create.name.clone(), columns)
+                    .await?;
                 Ok(QueryResult::Success(format!(
                     "Table {} created",
                     create.name
                 )))
             }
+            Statement::Begin
+            | Statement::Commit
+            | Statement::Rollback(_)
+            | Statement::Savepoint(_)
+            | Statement::ReleaseSavepoint(_) => {
+                unreachable!(
+                    "Engine::execute_statement handles transaction control itself and never \
+                     forwards these statements to Executor::execute"
+                )
+            }
         }
     }
 
-    fn evaluate_where(&self, row: &Row, where_clause: &WhereClause) -> bool {
-        let row_value = match row.get(&where_clause.column) {
-            Some(v) => v,
-            None => return false,
-        };
+    /// Resolve `stmt` into the [`Command`] `Engine` should hand to Raft for
+    /// replication instead of applying directly — column types parsed,
+    /// expressions evaluated, exactly as [`Self::execute`] would have done
+    /// before calling into `Storage`. Returns `None` for anything that
+    /// isn't a replicated write (reads, transaction control).
+    pub(crate) fn resolve_write(&self, stmt: &Statement) -> Result<Option<Command>, ExecutorError> {
+        match stmt {
+            Statement::Create(create) => Ok(Some(Command::CreateTable {
+                name: create.name.clone(),
+                columns: self.resolve_create(create)?,
+            })),
+            Statement::Drop(drop) => Ok(Some(Command::DropTable {
+                name: drop.name.clone(),
+            })),
+            // A versioned insert's result depends on the siblings already
+            // stored for its key, which only `Storage` itself knows — there's
+            // no way to resolve it down to a plain `Command` ahead of time
+            // the way a last-write-wins insert can be. Returning `None` here
+            // sends it down `execute_statement`'s local-apply fallback
+            // instead of through Raft replication.
+            Statement::Insert(insert) if insert.versioned.is_some() => Ok(None),
+            Statement::Insert(insert) => {
+                if insert.columns.len() != insert.values.len() {
+                    return Err(ExecutorError::ColumnCountMismatch);
+                }
+                Ok(Some(Command::InsertRow {
+                    table: insert.table.clone(),
+                    row: self.resolve_insert(insert)?,
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
 
-        let compare_value = match &where_clause.value {
-            Expr::Int(n) => Value::Int(*n),
-            Expr::Text(s) => Value::Text(s.clone()),
-            Expr::Null => Value::Null,
-            Expr::Column(_) | Expr::BinaryOp { .. } => return false, // Not supported in WHERE yet
-        };
+    fn resolve_create(
+        &self,
+        create: &CreateTable,
+    ) -> Result<HashMap<String, ColumnType>, ExecutorError> {
+        let mut columns = HashMap::new();
+        for col in &create.columns {
+            let col_type = match col.column_type.as_str() {
+                "INT" => ColumnType::Int,
+                "TEXT" => ColumnType::Text,
+                "FLOAT" => ColumnType::Float,
+                "BOOL" => ColumnType::Bool,
+                "TIMESTAMP" => ColumnType::Timestamp,
+                _ => return Err(ExecutorError::TypeMismatch(col.name.clone())),
+            };
+            columns.insert(col.name.clone(), col_type);
+        }
+        Ok(columns)
+    }
+
+    fn resolve_insert(&self, insert: &InsertStatement) -> Result<Row, ExecutorError> {
+        let meta = self
+            .storage
+            .get_table_meta(&insert.table)
+            .ok_or_else(|| StorageError::TableNotFound(insert.table.clone()))?;
 
-        match where_clause.operator {
-            CompareOp::Equal => row_value == &compare_value,
-            CompareOp::NotEqual => row_value != &compare_value,
-            CompareOp::LessThan => match (row_value, &compare_value) {
-                (Value::Int(a), Value::Int(b)) => a < b,
-                _ => false,
-            },
-            CompareOp::LessThanOrEqual => match (row_value, &compare_value) {
-                (Value::Int(a), Value::Int(b)) => a <= b,
-                _ => false,
-            },
-            CompareOp::GreaterThan => match (row_value, &compare_value) {
-                (Value::Int(a), Value::Int(b)) => a > b,
-                _ => false,
-            },
-            CompareOp::GreaterThanOrEqual => match (row_value, &compare_value) {
-                (Value::Int(a), Value::Int(b)) => a >= b,
-                _ => false,
-            },
+        let mut row = Row::new();
+        for (col_name, expr) in insert.columns.iter().zip(insert.values.iter()) {
+            let col_type = meta
+                .columns
+                .get(col_name)
+                .ok_or_else(|| ExecutorError::TypeMismatch(col_name.clone()))?;
+
+            let value = match (expr, col_type) {
+                (Expr::Int(n), ColumnType::Int) => Value::Int(*n),
+                (Expr::Text(s), ColumnType::Text) => Value::Text(s.clone()),
+                (Expr::Float(f), ColumnType::Float) => Value::Float(*f),
+                (Expr::Int(n), ColumnType::Float) => Value::Float(*n as f64),
+                (Expr::Bool(b), ColumnType::Bool) => Value::Bool(*b),
+                (Expr::Int(n), ColumnType::Timestamp) => Value::Timestamp(*n),
+                (Expr::Text(s), ColumnType::Timestamp) => {
+                    Value::Timestamp(parse_timestamp(s, col_name)?)
+                }
+                (Expr::Null, _) => Value::Null,
+                _ => return Err(ExecutorError::TypeMismatch(col_name.clone())),
+            };
+
+            row.insert(col_name.clone(), value);
+        }
+
+        Ok(row)
+    }
+
+    fn evaluate_where(&self, row: &Row, where_expr: &WhereExpr) -> Result<bool, ExecutorError> {
+        match where_expr {
+            WhereExpr::Comparison(clause) => self.evaluate_comparison(row, clause),
+            WhereExpr::And(left, right) => {
+                Ok(self.evaluate_where(row, left)? && self.evaluate_where(row, right)?)
+            }
+            WhereExpr::Or(left, right) => {
+                Ok(self.evaluate_where(row, left)? || self.evaluate_where(row, right)?)
+            }
+            WhereExpr::Not(inner) => Ok(!self.evaluate_where(row, inner)?),
         }
     }
 
+    /// Evaluate both sides of `where_clause` as arbitrary expressions (not
+    /// just `column op literal`) against `row`, so things like
+    /// `price * qty > 100` or `a = b` work the same as a plain column
+    /// comparison.
+    fn evaluate_comparison(
+        &self,
+        row: &Row,
+        where_clause: &WhereClause,
+    ) -> Result<bool, ExecutorError> {
+        let left = self.evaluate_expr(&where_clause.left, Some(row))?;
+        let right = self.evaluate_expr(&where_clause.right, Some(row))?;
+        Ok(compare_values(where_clause.operator, &left, &right))
+    }
+
     /// Evaluate an expression, optionally with a row context for column references
     fn evaluate_expr(&self, expr: &Expr, row: Option<&Row>) -> Result<Value, ExecutorError> {
         match expr {
             Expr::Int(n) => Ok(Value::Int(*n)),
+            Expr::Float(f) => Ok(Value::Float(*f)),
+            Expr::Bool(b) => Ok(Value::Bool(*b)),
             Expr::Text(s) => Ok(Value::Text(s.clone())),
             Expr::Null => Ok(Value::Null),
+            Expr::Placeholder(n) => Err(ExecutorError::UnboundParameter(*n)),
+            Expr::Aggregate { .. } => Err(ExecutorError::TypeMismatch(
+                "aggregate functions are only valid in a SELECT ... FROM query's projection"
+                    .to_string(),
+            )),
             Expr::Column(name) => {
                 if let Some(row) = row {
                     row.get(name)
@@ -247,9 +421,282 @@ impl<'a> Executor<'a> {
                         };
                         Ok(Value::Int(result))
                     }
+                    (Value::Float(a), Value::Float(b)) => {
+                        let result = match op {
+                            ArithmeticOp::Add => a + b,
+                            ArithmeticOp::Subtract => a - b,
+                            ArithmeticOp::Multiply => a * b,
+                            ArithmeticOp::Divide => {
+                                if b == 0.0 {
+                                    return Err(ExecutorError::DivisionByZero);
+                                }
+                                a / b
+                            }
+                        };
+                        Ok(Value::Float(result))
+                    }
+                    (Value::Int(a), Value::Float(b)) => {
+                        let a = a as f64;
+                        let result = match op {
+                            ArithmeticOp::Add => a + b,
+                            ArithmeticOp::Subtract => a - b,
+                            ArithmeticOp::Multiply => a * b,
+                            ArithmeticOp::Divide => {
+                                if b == 0.0 {
+                                    return Err(ExecutorError::DivisionByZero);
+                                }
+                                a / b
+                            }
+                        };
+                        Ok(Value::Float(result))
+                    }
+                    (Value::Float(a), Value::Int(b)) => {
+                        let b = b as f64;
+                        let result = match op {
+                            ArithmeticOp::Add => a + b,
+                            ArithmeticOp::Subtract => a - b,
+                            ArithmeticOp::Multiply => a * b,
+                            ArithmeticOp::Divide => {
+                                if b == 0.0 {
+                                    return Err(ExecutorError::DivisionByZero);
+                                }
+                                a / b
+                            }
+                        };
+                        Ok(Value::Float(result))
+                    }
                     _ => Err(ExecutorError::InvalidArithmetic),
                 }
             }
         }
     }
+
+    /// Partition `rows` by `select.group_by` (a single implicit group over
+    /// everything when it's empty, so a bare aggregate like
+    /// `SELECT COUNT(*) FROM t` still reduces the whole table to one row),
+    /// then evaluate `select.projection` once per group.
+    fn evaluate_grouped(
+        &self,
+        select: &SelectQuery,
+        rows: Vec<Row>,
+    ) -> Result<Vec<Row>, ExecutorError> {
+        let mut groups: Vec<(Vec<Value>, Vec<Row>)> = Vec::new();
+        for row in rows {
+            let key: Vec<Value> = select
+                .group_by
+                .iter()
+                .map(|col| row.get(col).cloned().unwrap_or(Value::Null))
+                .collect();
+
+            match groups.iter_mut().find(|(existing, _)| existing == &key) {
+                Some((_, members)) => members.push(row),
+                None => groups.push((key, vec![row])),
+            }
+        }
+
+        if groups.is_empty() && select.group_by.is_empty() {
+            groups.push((Vec::new(), Vec::new()));
+        }
+
+        let mut result = Vec::with_capacity(groups.len());
+        for (_, members) in &groups {
+            let mut out = Row::new();
+            for (i, item) in select.projection.iter().enumerate() {
+                let value = match &item.expr {
+                    Expr::Aggregate { func, arg } => {
+                        self.evaluate_aggregate(func, arg.as_deref(), members)?
+                    }
+                    other => self.evaluate_expr(other, members.first())?,
+                };
+                let col_name = item
+                    .alias
+                    .clone()
+                    .unwrap_or_else(|| default_projection_name(&item.expr, i));
+                out.insert(col_name, value);
+            }
+            result.push(out);
+        }
+        Ok(result)
+    }
+
+    /// Evaluate `func(arg)` over a single group's rows. `arg` is `None`
+    /// only for `COUNT(*)`, which counts rows rather than non-null values
+    /// of some column.
+    fn evaluate_aggregate(
+        &self,
+        func: &AggregateFunc,
+        arg: Option<&Expr>,
+        group: &[Row],
+    ) -> Result<Value, ExecutorError> {
+        if let AggregateFunc::Count = func {
+            let count = match arg {
+                Some(expr) => {
+                    let mut n = 0;
+                    for row in group {
+                        if !matches!(self.evaluate_expr(expr, Some(row))?, Value::Null) {
+                            n += 1;
+                        }
+                    }
+                    n
+                }
+                None => group.len(),
+            };
+            return Ok(Value::Int(count as i64));
+        }
+
+        let arg = arg.ok_or_else(|| {
+            ExecutorError::TypeMismatch(format!("{:?} requires a column argument", func))
+        })?;
+
+        let mut values = Vec::with_capacity(group.len());
+        for row in group {
+            let value = self.evaluate_expr(arg, Some(row))?;
+            if !matches!(value, Value::Null) {
+                values.push(value);
+            }
+        }
+
+        if values.is_empty() {
+            return Ok(Value::Null);
+        }
+
+        match func {
+            AggregateFunc::Min => Ok(values
+                .into_iter()
+                .min_by(|a, b| order_values(a, b).unwrap_or(std::cmp::Ordering::Equal))
+                .unwrap()),
+            AggregateFunc::Max => Ok(values
+                .into_iter()
+                .max_by(|a, b| order_values(a, b).unwrap_or(std::cmp::Ordering::Equal))
+                .unwrap()),
+            AggregateFunc::Sum | AggregateFunc::Avg => {
+                let all_int = values.iter().all(|v| matches!(v, Value::Int(_)));
+                let sum: f64 = values
+                    .iter()
+                    .map(|v| match v {
+                        Value::Int(n) => *n as f64,
+                        Value::Float(f) => *f,
+                        _ => 0.0,
+                    })
+                    .sum();
+                Ok(if matches!(func, AggregateFunc::Avg) {
+                    Value::Float(sum / values.len() as f64)
+                } else if all_int {
+                    Value::Int(sum as i64)
+                } else {
+                    Value::Float(sum)
+                })
+            }
+            AggregateFunc::Count => unreachable!("Count is handled above"),
+        }
+    }
+}
+
+/// The column name an unaliased projection item reports, mirroring how
+/// `Statement::SelectExpr` names unaliased expressions.
+fn default_projection_name(expr: &Expr, index: usize) -> String {
+    match expr {
+        Expr::Column(name) => name.clone(),
+        Expr::Aggregate { func, .. } => match func {
+            AggregateFunc::Count => "count".to_string(),
+            AggregateFunc::Sum => "sum".to_string(),
+            AggregateFunc::Avg => "avg".to_string(),
+            AggregateFunc::Min => "min".to_string(),
+            AggregateFunc::Max => "max".to_string(),
+        },
+        _ => format!("?column{}", index + 1),
+    }
+}
+
+/// Sort `rows` in place by `order_by`'s columns in order, each ascending or
+/// descending per its [`SortDir`]; a row missing a sort column compares
+/// equal to its neighbor on that key rather than erroring.
+fn sort_rows(rows: &mut [Row], order_by: &[(String, SortDir)]) {
+    rows.sort_by(|a, b| {
+        for (col, dir) in order_by {
+            let ordering = match (a.get(col), b.get(col)) {
+                (Some(x), Some(y)) => order_values(x, y).unwrap_or(std::cmp::Ordering::Equal),
+                _ => std::cmp::Ordering::Equal,
+            };
+            let ordering = match dir {
+                SortDir::Asc => ordering,
+                SortDir::Desc => ordering.reverse(),
+            };
+            if ordering != std::cmp::Ordering::Equal {
+                return ordering;
+            }
+        }
+        std::cmp::Ordering::Equal
+    });
+}
+
+/// Remove rows that duplicate an earlier row's full column set, preserving
+/// the first occurrence's position.
+fn dedup_rows(rows: &mut Vec<Row>) {
+    let mut seen: Vec<Row> = Vec::with_capacity(rows.len());
+    rows.retain(|row| {
+        if seen.iter().any(|s| s.data == row.data) {
+            false
+        } else {
+            seen.push(row.clone());
+            true
+        }
+    });
+}
+
+/// Evaluate a `WHERE` comparison's operator over two already-evaluated
+/// values. Follows SQL's three-valued logic collapsed to a filter
+/// predicate: a comparison where either side is `Value::Null` is never
+/// true, for any operator, including `!=`.
+fn compare_values(operator: CompareOp, left: &Value, right: &Value) -> bool {
+    if matches!(left, Value::Null) || matches!(right, Value::Null) {
+        return false;
+    }
+
+    match operator {
+        CompareOp::Equal => left == right,
+        CompareOp::NotEqual => left != right,
+        CompareOp::LessThan => order_values(left, right).is_some_and(|o| o.is_lt()),
+        CompareOp::LessThanOrEqual => order_values(left, right).is_some_and(|o| o.is_le()),
+        CompareOp::GreaterThan => order_values(left, right).is_some_and(|o| o.is_gt()),
+        CompareOp::GreaterThanOrEqual => order_values(left, right).is_some_and(|o| o.is_ge()),
+    }
+}
+
+/// Order two values for `<`/`<=`/`>`/`>=`, or `None` if they're not an
+/// orderable pair. Numeric types order across `Int`/`Float` the same way
+/// arithmetic does; `Text` orders lexicographically.
+fn order_values(left: &Value, right: &Value) -> Option<std::cmp::Ordering> {
+    match (left, right) {
+        (Value::Int(a), Value::Int(b)) => a.partial_cmp(b),
+        (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+        (Value::Int(a), Value::Float(b)) => (*a as f64).partial_cmp(b),
+        (Value::Float(a), Value::Int(b)) => a.partial_cmp(&(*b as f64)),
+        (Value::Timestamp(a), Value::Timestamp(b)) => a.partial_cmp(b),
+        (Value::Text(a), Value::Text(b)) => a.partial_cmp(b),
+        _ => None,
+    }
+}
+
+/// Parse a timestamp literal (RFC 3339, e.g. `2024-01-01T00:00:00Z`) into
+/// epoch milliseconds.
+fn parse_timestamp(s: &str, col_name: &str) -> Result<i64, ExecutorError> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.timestamp_millis())
+        .map_err(|_| ExecutorError::InvalidTimestamp(col_name.to_string()))
+}
+
+fn value_to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Int(i) => serde_json::Value::Number((*i).into()),
+        Value::Text(s) => serde_json::Value::String(s.clone()),
+        Value::Null => serde_json::Value::Null,
+        Value::Float(f) => serde_json::Number::from_f64(*f)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        Value::Bool(b) => serde_json::Value::Bool(*b),
+        Value::Timestamp(ts) => DateTime::<Utc>::from_timestamp_millis(*ts)
+            .map(|dt| serde_json::Value::String(dt.to_rfc3339()))
+            .unwrap_or(serde_json::Value::Null),
+    }
 }
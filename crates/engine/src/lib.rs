@@ -0,0 +1,18 @@
+mod cluster;
+mod engine;
+mod executor;
+mod raft;
+
+#[cfg(feature = "slt")]
+pub mod slt;
+
+pub use cluster::{ClusterError, ClusterMap, ClusterNodeId, ClusterTransport, NodeAddr};
+pub use engine::{Engine, EngineError};
+pub use executor::{Executor, ExecutorError, QueryResult, SqlState};
+pub use raft::{
+    AppendEntriesArgs, AppendEntriesReply, Command, NodeId, RaftError, RaftNode, RaftTransport,
+    RequestVoteArgs, RequestVoteReply,
+};
+
+#[cfg(feature = "slt")]
+pub use slt::{run_file, run_str, SltError, SortMode};
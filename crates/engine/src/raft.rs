@@ -0,0 +1,468 @@
+//! A minimal Raft consensus layer so an [`crate::Engine`] can run as a
+//! replicated, multi-node cluster instead of against one local `Storage`.
+//!
+//! Each write statement (`CREATE`/`DROP`/`INSERT`) is resolved to a
+//! [`Command`], appended to the replicated log, and only applied to
+//! `Storage` once a majority of the cluster has it durably in their own
+//! log — the commit-then-apply split `storage::wal` already uses for crash
+//! recovery, just replicated across machines instead of across a restart.
+//! Reads are only served by the leader; everyone else answers with
+//! [`RaftError::NotLeader`] so the caller can redirect there.
+//!
+//! This implements the core consensus state machine (leader election, log
+//! replication, commit-index advancement) and leaves how RPCs actually
+//! reach a peer behind the [`RaftTransport`] trait, the same way
+//! `storage::StorageBackend` abstracts where bytes live.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use storage::{ColumnType, Row, Storage, StorageError};
+use thiserror::Error;
+
+/// Identifies a node within a cluster. Nodes are expected to agree on a
+/// stable id (e.g. derived from their listen address) out of band.
+pub type NodeId = u64;
+
+#[derive(Error, Debug)]
+pub enum RaftError {
+    #[error("not the leader; last known leader is {0:?}")]
+    NotLeader(Option<NodeId>),
+    #[error("storage error: {0}")]
+    Storage(#[from] StorageError),
+    #[error("persistence error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] bincode::Error),
+    #[error("transport error talking to node {0}: {1}")]
+    Transport(NodeId, String),
+}
+
+/// A single mutating operation, already resolved (column types parsed,
+/// expressions evaluated) the way `Executor` would have resolved it.
+/// Mirrors `storage::wal::WalRecord`'s shape — both exist to let
+/// committed-but-not-yet-durable work survive something going wrong, a
+/// crash there, a lost node here.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Command {
+    CreateTable {
+        name: String,
+        columns: HashMap<String, ColumnType>,
+    },
+    DropTable {
+        name: String,
+    },
+    InsertRow {
+        table: String,
+        row: Row,
+    },
+}
+
+impl Command {
+    /// Apply this command to `storage`, the same way `Executor::execute`
+    /// would have applied the statement it was resolved from.
+    async fn apply(&self, storage: &Storage) -> Result<(), StorageError> {
+        match self {
+            Command::CreateTable { name, columns } => {
+                storage.create_table(name.clone(), columns.clone()).await
+            }
+            Command::DropTable { name } => storage.drop_table(name).await,
+            Command::InsertRow { table, row } => storage.insert_row(table, row.clone()).await,
+        }
+    }
+}
+
+/// A single entry in the replicated log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub term: u64,
+    pub index: u64,
+    pub command: Command,
+}
+
+/// The subset of Raft state that must survive a restart: the current term,
+/// who this node voted for in it, and the log itself. Persisted as one
+/// bincode blob alongside `catalog.bin`, the same way `Storage` persists
+/// its catalog — Raft state changes far less often than every write, so a
+/// write-the-whole-file save is simpler than a second WAL.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct HardState {
+    current_term: u64,
+    voted_for: Option<NodeId>,
+    log: Vec<LogEntry>,
+}
+
+/// File Raft's [`HardState`] is persisted under, next to `catalog.bin` in
+/// the node's data directory.
+const RAFT_STATE_FILE: &str = "raft_state.bin";
+
+impl HardState {
+    fn load(path: &Path) -> Result<Self, RaftError> {
+        match std::fs::read(path) {
+            Ok(bytes) => Ok(bincode::deserialize(&bytes)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, path: &Path) -> Result<(), RaftError> {
+        let bytes = bincode::serialize(self)?;
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+}
+
+/// What a node's [`RaftNode`] believes its own role to be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Follower,
+    Candidate,
+    Leader,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestVoteArgs {
+    pub term: u64,
+    pub candidate_id: NodeId,
+    pub last_log_index: u64,
+    pub last_log_term: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestVoteReply {
+    pub term: u64,
+    pub vote_granted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppendEntriesArgs {
+    pub term: u64,
+    pub leader_id: NodeId,
+    pub prev_log_index: u64,
+    pub prev_log_term: u64,
+    pub entries: Vec<LogEntry>,
+    pub leader_commit: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppendEntriesReply {
+    pub term: u64,
+    pub success: bool,
+}
+
+/// How a [`RaftNode`] talks to its peers. Kept as a trait, the same way
+/// `storage::StorageBackend` abstracts where bytes live, so tests can wire
+/// up in-process peers without an actual socket while production code
+/// drives it over TCP.
+#[async_trait]
+pub trait RaftTransport: Send + Sync {
+    async fn request_vote(
+        &self,
+        peer: NodeId,
+        args: RequestVoteArgs,
+    ) -> Result<RequestVoteReply, RaftError>;
+
+    async fn append_entries(
+        &self,
+        peer: NodeId,
+        args: AppendEntriesArgs,
+    ) -> Result<AppendEntriesReply, RaftError>;
+}
+
+/// A Raft node driving one `Storage` replica.
+///
+/// `RaftNode` only handles consensus: deciding what's committed and in
+/// what order. Applying a committed [`Command`] to `Storage` — the
+/// state-machine half of Raft — happens inline in [`Self::propose`] and
+/// [`Self::handle_append_entries`] via [`Self::apply_committed`], both of
+/// which call [`Command::apply`] once an entry's index is at or below
+/// `commit_index`.
+pub struct RaftNode {
+    id: NodeId,
+    peers: Vec<NodeId>,
+    transport: Arc<dyn RaftTransport>,
+    role: Role,
+    leader_id: Option<NodeId>,
+    hard_state: HardState,
+    commit_index: u64,
+    last_applied: u64,
+    state_path: PathBuf,
+}
+
+impl RaftNode {
+    /// Load (or initialize) persisted Raft state for `id` from
+    /// `data_dir`/`raft_state.bin` and start as a follower with no known
+    /// leader yet.
+    pub fn open(
+        id: NodeId,
+        peers: Vec<NodeId>,
+        transport: Arc<dyn RaftTransport>,
+        data_dir: &Path,
+    ) -> Result<Self, RaftError> {
+        let state_path = data_dir.join(RAFT_STATE_FILE);
+        let hard_state = HardState::load(&state_path)?;
+
+        Ok(Self {
+            id,
+            peers,
+            transport,
+            role: Role::Follower,
+            leader_id: None,
+            hard_state,
+            commit_index: 0,
+            last_applied: 0,
+            state_path,
+        })
+    }
+
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.role == Role::Leader
+    }
+
+    /// The node this one currently believes is the leader, for a follower
+    /// to redirect a client toward.
+    pub fn leader_hint(&self) -> Option<NodeId> {
+        self.leader_id
+    }
+
+    /// Add a node to the cluster's peer set. Takes effect on the next
+    /// `propose`/heartbeat round; this intentionally skips the
+    /// joint-consensus dance real Raft uses to stay safe mid-change, since
+    /// poubelle clusters are expected to be resized rarely and by hand.
+    pub fn add_member(&mut self, peer: NodeId) {
+        if peer != self.id && !self.peers.contains(&peer) {
+            self.peers.push(peer);
+        }
+    }
+
+    /// Remove a node from the cluster's peer set.
+    pub fn remove_member(&mut self, peer: NodeId) {
+        self.peers.retain(|p| *p != peer);
+    }
+
+    pub fn members(&self) -> &[NodeId] {
+        &self.peers
+    }
+
+    fn last_log_index(&self) -> u64 {
+        self.hard_state.log.last().map(|e| e.index).unwrap_or(0)
+    }
+
+    fn last_log_term(&self) -> u64 {
+        self.hard_state.log.last().map(|e| e.term).unwrap_or(0)
+    }
+
+    fn term_at(&self, index: u64) -> u64 {
+        if index == 0 {
+            return 0;
+        }
+        self.hard_state
+            .log
+            .iter()
+            .find(|e| e.index == index)
+            .map(|e| e.term)
+            .unwrap_or(0)
+    }
+
+    /// A majority of the cluster, counting this node.
+    fn quorum(&self) -> usize {
+        (self.peers.len() + 1) / 2 + 1
+    }
+
+    fn step_down(&mut self, term: u64) {
+        self.hard_state.current_term = term;
+        self.hard_state.voted_for = None;
+        self.role = Role::Follower;
+        self.leader_id = None;
+    }
+
+    /// Become a candidate, vote for self, and request votes from every
+    /// peer; becomes leader if a majority (including self) grants one.
+    pub async fn start_election(&mut self) -> Result<(), RaftError> {
+        self.role = Role::Candidate;
+        self.hard_state.current_term += 1;
+        self.hard_state.voted_for = Some(self.id);
+        self.leader_id = None;
+        self.hard_state.save(&self.state_path)?;
+
+        let args = RequestVoteArgs {
+            term: self.hard_state.current_term,
+            candidate_id: self.id,
+            last_log_index: self.last_log_index(),
+            last_log_term: self.last_log_term(),
+        };
+
+        let mut votes = 1; // this node votes for itself
+        for peer in self.peers.clone() {
+            match self.transport.request_vote(peer, args.clone()).await {
+                Ok(reply) if reply.term > self.hard_state.current_term => {
+                    self.step_down(reply.term);
+                    self.hard_state.save(&self.state_path)?;
+                    return Ok(());
+                }
+                Ok(reply) if reply.vote_granted => votes += 1,
+                _ => {}
+            }
+        }
+
+        self.role = if votes >= self.quorum() {
+            self.leader_id = Some(self.id);
+            Role::Leader
+        } else {
+            Role::Follower
+        };
+
+        Ok(())
+    }
+
+    /// Append `command` to the log, replicate it to a majority of peers,
+    /// and — once committed — apply it to `storage`. Only the leader can
+    /// propose; everyone else returns [`RaftError::NotLeader`] with the
+    /// last known leader so the caller can redirect there.
+    pub async fn propose(&mut self, command: Command, storage: &Storage) -> Result<u64, RaftError> {
+        if !self.is_leader() {
+            return Err(RaftError::NotLeader(self.leader_id));
+        }
+
+        let entry = LogEntry {
+            term: self.hard_state.current_term,
+            index: self.last_log_index() + 1,
+            command,
+        };
+        self.hard_state.log.push(entry.clone());
+        self.hard_state.save(&self.state_path)?;
+
+        let mut acks = 1; // the leader already has it locally
+        for peer in self.peers.clone() {
+            let args = AppendEntriesArgs {
+                term: self.hard_state.current_term,
+                leader_id: self.id,
+                prev_log_index: entry.index.saturating_sub(1),
+                prev_log_term: self.term_at(entry.index.saturating_sub(1)),
+                entries: vec![entry.clone()],
+                leader_commit: self.commit_index,
+            };
+
+            match self.transport.append_entries(peer, args).await {
+                Ok(reply) if reply.success => acks += 1,
+                Ok(reply) if reply.term > self.hard_state.current_term => {
+                    self.step_down(reply.term);
+                    self.hard_state.save(&self.state_path)?;
+                    return Err(RaftError::NotLeader(None));
+                }
+                _ => {}
+            }
+        }
+
+        if acks >= self.quorum() {
+            self.commit_index = entry.index;
+            self.apply_committed(storage).await?;
+        }
+
+        Ok(entry.index)
+    }
+
+    /// Apply every log entry between `last_applied` and `commit_index` to
+    /// `storage`, in order — the Raft state-machine hook.
+    async fn apply_committed(&mut self, storage: &Storage) -> Result<(), RaftError> {
+        while self.last_applied < self.commit_index {
+            self.last_applied += 1;
+            if let Some(entry) = self
+                .hard_state
+                .log
+                .iter()
+                .find(|e| e.index == self.last_applied)
+            {
+                entry.command.apply(storage).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle an incoming `RequestVote` RPC from a candidate.
+    pub fn handle_request_vote(&mut self, args: RequestVoteArgs) -> RequestVoteReply {
+        if args.term < self.hard_state.current_term {
+            return RequestVoteReply {
+                term: self.hard_state.current_term,
+                vote_granted: false,
+            };
+        }
+
+        if args.term > self.hard_state.current_term {
+            self.step_down(args.term);
+        }
+
+        let already_voted_elsewhere = self
+            .hard_state
+            .voted_for
+            .is_some_and(|v| v != args.candidate_id);
+        let candidate_up_to_date = args.last_log_term > self.last_log_term()
+            || (args.last_log_term == self.last_log_term()
+                && args.last_log_index >= self.last_log_index());
+
+        let vote_granted = !already_voted_elsewhere && candidate_up_to_date;
+        if vote_granted {
+            self.hard_state.voted_for = Some(args.candidate_id);
+        }
+        let _ = self.hard_state.save(&self.state_path);
+
+        RequestVoteReply {
+            term: self.hard_state.current_term,
+            vote_granted,
+        }
+    }
+
+    /// Handle an incoming `AppendEntries` RPC (heartbeat or replication)
+    /// from the leader.
+    pub async fn handle_append_entries(
+        &mut self,
+        args: AppendEntriesArgs,
+        storage: &Storage,
+    ) -> Result<AppendEntriesReply, RaftError> {
+        if args.term < self.hard_state.current_term {
+            return Ok(AppendEntriesReply {
+                term: self.hard_state.current_term,
+                success: false,
+            });
+        }
+
+        if args.term > self.hard_state.current_term {
+            self.step_down(args.term);
+        }
+        self.role = Role::Follower;
+        self.leader_id = Some(args.leader_id);
+
+        if args.prev_log_index > 0 && self.term_at(args.prev_log_index) != args.prev_log_term {
+            return Ok(AppendEntriesReply {
+                term: self.hard_state.current_term,
+                success: false,
+            });
+        }
+
+        self.hard_state
+            .log
+            .retain(|e| e.index <= args.prev_log_index);
+        self.hard_state.log.extend(args.entries);
+        self.hard_state.save(&self.state_path)?;
+
+        if args.leader_commit > self.commit_index {
+            self.commit_index = args.leader_commit.min(self.last_log_index());
+            self.apply_committed(storage).await?;
+        }
+
+        Ok(AppendEntriesReply {
+            term: self.hard_state.current_term,
+            success: true,
+        })
+    }
+}
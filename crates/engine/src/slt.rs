@@ -0,0 +1,558 @@
+//! A minimal [sqllogictest](https://www.sqlite.org/sqllogictest/doc/trunk/about.wiki)-style
+//! record runner: parses `.slt` files and runs them against [`Executor::execute`],
+//! so the SQL engine can be validated against a corpus of declarative cases
+//! instead of hand-written Rust asserts.
+//!
+//! Supports the two core record kinds:
+//! - `statement ok` / `statement error <regex>` — run a statement, assert
+//!   success or that the resulting error's message matches `<regex>`.
+//! - `query <typestring> <sortmode>` followed by the SQL, a `----`
+//!   separator, and the expected result, one value per line in row-major
+//!   order — run the query, render its rows to canonical text, and compare.
+//!
+//! A `hash-threshold N` record (outside any statement/query) changes how
+//! later `query` records in the file are compared: result sets with more
+//! than `N` rows are checked against an MD5 digest instead of row-by-row,
+//! written as `<count> values hashing to <hex>`.
+//!
+//! `Row` is a `HashMap`, so it carries no column order of its own. For a
+//! `SELECT` with an explicit column list (or an expression `SELECT`), that
+//! list supplies the order values are rendered in; for `SELECT *`, column
+//! names are sorted alphabetically so output is still deterministic.
+
+use crate::executor::{Executor, QueryResult};
+use md5::{Digest, Md5};
+use parser::{Parser, Statement};
+use regex::Regex;
+use storage::{Row, Value};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SltError {
+    #[error("{path}:{line}: {message}")]
+    MalformedRecord {
+        path: String,
+        line: usize,
+        message: String,
+    },
+    #[error("{path}:{line}: invalid regex {pattern:?}: {source}")]
+    InvalidRegex {
+        path: String,
+        line: usize,
+        pattern: String,
+        #[source]
+        source: regex::Error,
+    },
+    #[error("{path}:{line}: {message}")]
+    Mismatch {
+        path: String,
+        line: usize,
+        message: String,
+    },
+    #[error("IO error reading {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortMode {
+    NoSort,
+    RowSort,
+    ValueSort,
+}
+
+impl SortMode {
+    fn parse(path: &str, line: usize, s: &str) -> Result<Self, SltError> {
+        match s {
+            "nosort" => Ok(SortMode::NoSort),
+            "rowsort" => Ok(SortMode::RowSort),
+            "valuesort" => Ok(SortMode::ValueSort),
+            other => Err(SltError::MalformedRecord {
+                path: path.to_string(),
+                line,
+                message: format!("unknown sort mode {:?}", other),
+            }),
+        }
+    }
+}
+
+/// Run every `.slt` record in `content`, stopping at the first failure.
+/// `path` is only used to label errors.
+pub async fn run_str(
+    path: &str,
+    content: &str,
+    executor: &mut Executor<'_>,
+) -> Result<(), SltError> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut hash_threshold: usize = 0;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line_no = i + 1;
+        let line = lines[i].trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            i += 1;
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("hash-threshold ") {
+            hash_threshold = rest.trim().parse().map_err(|_| SltError::MalformedRecord {
+                path: path.to_string(),
+                line: line_no,
+                message: format!("invalid hash-threshold: {:?}", rest),
+            })?;
+            i += 1;
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("statement ") {
+            i += 1;
+            let sql = consume_until_blank(&lines, &mut i);
+            run_statement(path, line_no, rest, &sql, executor).await?;
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("query ") {
+            i += 1;
+            let sql = consume_until_separator(path, line_no, &lines, &mut i)?;
+            let expected = consume_until_blank(&lines, &mut i);
+            let expected: Vec<String> = expected.lines().map(|l| l.to_string()).collect();
+            run_query(
+                path,
+                line_no,
+                rest,
+                &sql,
+                &expected,
+                hash_threshold,
+                executor,
+            )
+            .await?;
+            continue;
+        }
+
+        return Err(SltError::MalformedRecord {
+            path: path.to_string(),
+            line: line_no,
+            message: format!("unrecognized record: {:?}", line),
+        });
+    }
+
+    Ok(())
+}
+
+/// Read [`run_str`]'s `path` from disk and run it.
+pub async fn run_file(path: &std::path::Path, executor: &mut Executor<'_>) -> Result<(), SltError> {
+    let path_str = path.display().to_string();
+    let content = std::fs::read_to_string(path).map_err(|source| SltError::Io {
+        path: path_str.clone(),
+        source,
+    })?;
+    run_str(&path_str, &content, executor).await
+}
+
+/// Collect lines from `lines[*i]` up to (not including) the next blank
+/// line or EOF, advancing `*i` past it.
+fn consume_until_blank(lines: &[&str], i: &mut usize) -> String {
+    let start = *i;
+    while *i < lines.len() && !lines[*i].trim().is_empty() {
+        *i += 1;
+    }
+    let sql = lines[start..*i].join("\n");
+    if *i < lines.len() {
+        *i += 1; // skip the blank separator line
+    }
+    sql
+}
+
+/// Collect a query record's SQL, up to the `----` separator, advancing
+/// `*i` past it.
+fn consume_until_separator(
+    path: &str,
+    record_line: usize,
+    lines: &[&str],
+    i: &mut usize,
+) -> Result<String, SltError> {
+    let start = *i;
+    while *i < lines.len() && lines[*i].trim() != "----" {
+        *i += 1;
+    }
+    if *i >= lines.len() {
+        return Err(SltError::MalformedRecord {
+            path: path.to_string(),
+            line: record_line,
+            message: "query record missing ---- separator".to_string(),
+        });
+    }
+    let sql = lines[start..*i].join("\n");
+    *i += 1; // skip "----"
+    Ok(sql)
+}
+
+async fn run_statement(
+    path: &str,
+    line: usize,
+    directive: &str,
+    sql: &str,
+    executor: &mut Executor<'_>,
+) -> Result<(), SltError> {
+    let result = execute_sql(sql, executor).await;
+
+    if directive == "ok" {
+        return result.map(|_| ()).map_err(|message| SltError::Mismatch {
+            path: path.to_string(),
+            line,
+            message: format!("expected statement to succeed, got error: {}", message),
+        });
+    }
+
+    if let Some(pattern) = directive.strip_prefix("error ") {
+        let re = Regex::new(pattern).map_err(|source| SltError::InvalidRegex {
+            path: path.to_string(),
+            line,
+            pattern: pattern.to_string(),
+            source,
+        })?;
+        return match result {
+            Ok(_) => Err(SltError::Mismatch {
+                path: path.to_string(),
+                line,
+                message: "expected statement to fail, but it succeeded".to_string(),
+            }),
+            Err(message) if re.is_match(&message) => Ok(()),
+            Err(message) => Err(SltError::Mismatch {
+                path: path.to_string(),
+                line,
+                message: format!("error {:?} did not match /{}/", message, pattern),
+            }),
+        };
+    }
+
+    Err(SltError::MalformedRecord {
+        path: path.to_string(),
+        line,
+        message: format!("unrecognized statement directive: {:?}", directive),
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_query(
+    path: &str,
+    line: usize,
+    header: &str,
+    sql: &str,
+    expected: &[String],
+    hash_threshold: usize,
+    executor: &mut Executor<'_>,
+) -> Result<(), SltError> {
+    let mut parts = header.split_whitespace();
+    let typestring = parts.next().ok_or_else(|| SltError::MalformedRecord {
+        path: path.to_string(),
+        line,
+        message: "query record missing type string".to_string(),
+    })?;
+    for c in typestring.chars() {
+        if !"ITRB".contains(c) {
+            return Err(SltError::MalformedRecord {
+                path: path.to_string(),
+                line,
+                message: format!("unknown type character {:?} in {:?}", c, typestring),
+            });
+        }
+    }
+    let sort_mode = match parts.next() {
+        Some(s) => SortMode::parse(path, line, s)?,
+        None => SortMode::NoSort,
+    };
+
+    let statement = Parser::new(sql).parse().map_err(|e| SltError::Mismatch {
+        path: path.to_string(),
+        line,
+        message: format!("expected query to succeed, got parse error: {}", e),
+    })?;
+    let rows = match executor.execute(statement.clone()).await {
+        Ok(QueryResult::Rows(rows)) => rows,
+        Ok(_) => {
+            return Err(SltError::Mismatch {
+                path: path.to_string(),
+                line,
+                message: "statement did not produce rows".to_string(),
+            })
+        }
+        Err(e) => {
+            return Err(SltError::Mismatch {
+                path: path.to_string(),
+                line,
+                message: format!("expected query to succeed, got error: {}", e),
+            })
+        }
+    };
+
+    let columns = column_order(&statement, rows.first());
+    let mut actual: Vec<String> = rows
+        .iter()
+        .flat_map(|row| columns.iter().map(|col| canonical_value(row.get(col))))
+        .collect();
+
+    if let Some((count, digest)) = parse_hash_expectation(expected) {
+        if hash_threshold == 0 || rows.len() <= hash_threshold {
+            return Err(SltError::Mismatch {
+                path: path.to_string(),
+                line,
+                message: "result hashed below hash-threshold".to_string(),
+            });
+        }
+        apply_sort(&mut actual, columns.len(), sort_mode);
+        let actual_digest = md5_hex(&actual.join("\n"));
+        if actual.len() != count || actual_digest != digest {
+            return Err(SltError::Mismatch {
+                path: path.to_string(),
+                line,
+                message: format!(
+                    "expected {} values hashing to {}, got {} values hashing to {}",
+                    count,
+                    digest,
+                    actual.len(),
+                    actual_digest
+                ),
+            });
+        }
+        return Ok(());
+    }
+
+    if hash_threshold > 0 && rows.len() > hash_threshold {
+        return Err(SltError::Mismatch {
+            path: path.to_string(),
+            line,
+            message: format!(
+                "{} rows exceed hash-threshold {}; expected a hashed result",
+                rows.len(),
+                hash_threshold
+            ),
+        });
+    }
+
+    apply_sort(&mut actual, columns.len(), sort_mode);
+    let mut expected = expected.to_vec();
+    apply_sort(&mut expected, columns.len(), sort_mode);
+
+    if actual != expected {
+        return Err(SltError::Mismatch {
+            path: path.to_string(),
+            line,
+            message: format!("expected {:?}, got {:?}", expected, actual),
+        });
+    }
+
+    Ok(())
+}
+
+/// Parse an expected section of the form `<count> values hashing to <hex>`,
+/// the format `hash-threshold` records compare against.
+fn parse_hash_expectation(expected: &[String]) -> Option<(usize, String)> {
+    if expected.len() != 1 {
+        return None;
+    }
+    let line = expected[0].trim();
+    let mut parts = line.split_whitespace();
+    let count: usize = parts.next()?.parse().ok()?;
+    if parts.next()? != "values" || parts.next()? != "hashing" || parts.next()? != "to" {
+        return None;
+    }
+    let digest = parts.next()?.to_string();
+    if parts.next().is_some()
+        || digest.len() != 32
+        || !digest.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return None;
+    }
+    Some((count, digest))
+}
+
+fn md5_hex(s: &str) -> String {
+    let mut hasher = Md5::new();
+    hasher.update(s.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Sort `values` (a flattened, row-major list of `ncols`-wide rows) as
+/// `mode` dictates. `rowsort` keeps each row's values together and orders
+/// rows by comparing their values column by column; `valuesort` ignores row boundaries
+/// entirely and sorts every value independently.
+fn apply_sort(values: &mut Vec<String>, ncols: usize, mode: SortMode) {
+    match mode {
+        SortMode::NoSort => {}
+        SortMode::ValueSort => values.sort(),
+        SortMode::RowSort => {
+            if ncols == 0 {
+                return;
+            }
+            let mut rows: Vec<&[String]> = values.chunks(ncols).collect();
+            rows.sort();
+            *values = rows.into_iter().flatten().cloned().collect();
+        }
+    }
+}
+
+/// The column order to render a result row's values in. `Row` is a
+/// `HashMap`, so for `SELECT *` (no explicit column list) there's no
+/// original order to recover; column names are sorted alphabetically
+/// instead so the rendering is at least deterministic.
+fn column_order(statement: &Statement, first_row: Option<&Row>) -> Vec<String> {
+    match statement {
+        Statement::Select(select) if !select.columns.contains(&"*".to_string()) => {
+            select.columns.clone()
+        }
+        Statement::SelectExpr(select_expr) => select_expr
+            .expressions
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                item.alias
+                    .clone()
+                    .unwrap_or_else(|| format!("?column{}", i + 1))
+            })
+            .collect(),
+        _ => {
+            let mut keys: Vec<String> = first_row
+                .map(|row| row.data.keys().cloned().collect())
+                .unwrap_or_default();
+            keys.sort();
+            keys
+        }
+    }
+}
+
+fn canonical_value(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => "NULL".to_string(),
+        Some(Value::Int(n)) => n.to_string(),
+        Some(Value::Float(f)) => format!("{:.3}", f),
+        Some(Value::Bool(b)) => if *b { "1" } else { "0" }.to_string(),
+        Some(Value::Text(s)) if s.is_empty() => "(empty)".to_string(),
+        Some(Value::Text(s)) => s.clone(),
+        Some(Value::Timestamp(ts)) => ts.to_string(),
+    }
+}
+
+/// Parse and execute `sql` as a single statement, flattening any failure
+/// (parse or execution) to its display text — the shape `statement`/`query`
+/// records compare against.
+async fn execute_sql(sql: &str, executor: &mut Executor<'_>) -> Result<QueryResult, String> {
+    let statement = Parser::new(sql).parse().map_err(|e| e.to_string())?;
+    executor.execute(statement).await.map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use storage::{Storage, StorageBuilder};
+
+    // `StorageBuilder::memory` still keeps its WAL on local disk, so each
+    // test gets its own directory under `temp_dir()` — a monotonic counter
+    // is enough to keep parallel `#[tokio::test]` runs from colliding.
+    async fn test_storage() -> Storage {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "poubelle-slt-test-{}",
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        StorageBuilder::memory(path).open().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_statement_ok_and_query_pass() {
+        let storage = test_storage().await;
+        let mut executor = Executor::new(&storage);
+
+        let script = "\
+statement ok
+CREATE TABLE t (id INT, name TEXT)
+
+statement ok
+INSERT INTO t (id, name) VALUES (1, 'a')
+
+query IT nosort
+SELECT id, name FROM t
+----
+1
+a
+";
+
+        run_str("test.slt", script, &mut executor).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_statement_error_directive_matches_message() {
+        let storage = test_storage().await;
+        let mut executor = Executor::new(&storage);
+
+        let script = "\
+statement error Table not found
+SELECT * FROM nonexistent
+";
+
+        run_str("test.slt", script, &mut executor).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_query_mismatch_is_reported_with_line_number() {
+        let storage = test_storage().await;
+        let mut executor = Executor::new(&storage);
+
+        let script = "\
+statement ok
+CREATE TABLE t (id INT)
+
+statement ok
+INSERT INTO t (id) VALUES (1)
+
+query I nosort
+SELECT id FROM t
+----
+2
+";
+
+        let err = run_str("test.slt", script, &mut executor)
+            .await
+            .expect_err("expected row mismatch to be reported");
+        match err {
+            SltError::Mismatch { line, .. } => assert_eq!(line, 7),
+            other => panic!("expected Mismatch, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hash_threshold_below_threshold_is_rejected() {
+        let storage = test_storage().await;
+        let mut executor = Executor::new(&storage);
+
+        // `hash-threshold 1` makes a 1-row result ineligible for hash
+        // comparison (it's not *more* than the threshold), so a hashed
+        // expectation here should be reported as a mismatch rather than
+        // silently accepted.
+        let script = "\
+hash-threshold 1
+
+statement ok
+CREATE TABLE t (id INT)
+
+statement ok
+INSERT INTO t (id) VALUES (1)
+
+query I nosort
+SELECT id FROM t
+----
+1 values hashing to d41d8cd98f00b204e9800998ecf8427e
+";
+
+        let err = run_str("test.slt", script, &mut executor)
+            .await
+            .expect_err("expected hash comparison to be rejected below the threshold");
+        assert!(matches!(err, SltError::Mismatch { .. }));
+    }
+}
@@ -1,9 +1,15 @@
 #[derive(Debug, Clone, PartialEq)]
 pub enum Statement {
     Select(SelectQuery),
+    SelectExpr(SelectExprQuery),
     Insert(InsertStatement),
     Create(CreateTable),
     Drop(DropTable),
+    Begin,
+    Commit,
+    Rollback(Option<String>),
+    Savepoint(String),
+    ReleaseSavepoint(String),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -14,12 +20,46 @@ pub struct DropTable {
 #[derive(Debug, Clone, PartialEq)]
 pub struct SelectQuery {
     pub columns: Vec<String>,
+    /// The select list as parsed expressions, parallel to `columns` but
+    /// retaining aggregate calls and aliases. Empty for `SELECT *`.
+    pub projection: Vec<SelectItem>,
     pub table: String,
-    pub where_clause: Option<WhereClause>,
+    pub where_clause: Option<WhereExpr>,
+    pub group_by: Vec<String>,
+    pub order_by: Vec<(String, SortDir)>,
+    pub distinct: bool,
     pub limit: Option<usize>,
     pub format: OutputFormat,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortDir {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AggregateFunc {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+/// A `SELECT` with no `FROM` clause, e.g. `SELECT 1 + 2 AS total;`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectExprQuery {
+    pub expressions: Vec<SelectItem>,
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectItem {
+    pub expr: Expr,
+    pub alias: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum OutputFormat {
     Debug,
@@ -28,9 +68,19 @@ pub enum OutputFormat {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct WhereClause {
-    pub column: String,
+    pub left: Expr,
     pub operator: CompareOp,
-    pub value: Expr,
+    pub right: Expr,
+}
+
+/// A boolean combination of `WHERE` comparisons, built from `AND`/`OR`/`NOT`
+/// and parenthesized groups.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WhereExpr {
+    Comparison(WhereClause),
+    And(Box<WhereExpr>, Box<WhereExpr>),
+    Or(Box<WhereExpr>, Box<WhereExpr>),
+    Not(Box<WhereExpr>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -60,11 +110,47 @@ pub struct InsertStatement {
     pub table: String,
     pub columns: Vec<String>,
     pub values: Vec<Expr>,
+    /// `VERSIONED KEY '<key>' WRITER '<writer>'`, if present: route this
+    /// insert through dotted-version-vector causality tracking
+    /// (`Storage::insert_versioned_row`) instead of a plain last-write-wins
+    /// write.
+    pub versioned: Option<VersionedInsert>,
+}
+
+/// The `KEY`/`WRITER` pair parsed off an `INSERT ... VERSIONED` statement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionedInsert {
+    pub key: String,
+    pub writer: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArithmeticOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
     Int(i64),
+    Float(f64),
+    Bool(bool),
     Text(String),
     Null,
+    Column(String),
+    /// A positional bind parameter, e.g. `$1`, as used in prepared statements.
+    Placeholder(usize),
+    BinaryOp {
+        left: Box<Expr>,
+        op: ArithmeticOp,
+        right: Box<Expr>,
+    },
+    /// An aggregate function call, e.g. `COUNT(*)` or `SUM(amount)`. `arg` is
+    /// `None` only for `COUNT(*)`.
+    Aggregate {
+        func: AggregateFunc,
+        arg: Option<Box<Expr>>,
+    },
 }
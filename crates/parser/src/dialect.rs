@@ -0,0 +1,122 @@
+use crate::lexer::Token;
+
+/// Defines the keyword and identifier rules a [`crate::lexer::Lexer`] should
+/// follow. Different SQL dialects disagree on which characters can quote an
+/// identifier and on the exact keyword set, so this is pulled out behind a
+/// trait rather than hardcoded into the lexer.
+pub trait Dialect: Send + Sync {
+    /// Human-readable name, useful for diagnostics.
+    fn name(&self) -> &'static str;
+
+    /// Resolve a case-folded identifier to a keyword token, if this dialect
+    /// treats it as a reserved word.
+    fn keyword(&self, uppercased: &str) -> Option<Token>;
+
+    /// Whether `ch` can start an unquoted identifier.
+    fn is_identifier_start(&self, ch: char) -> bool {
+        ch.is_alphabetic() || ch == '_'
+    }
+
+    /// Whether `ch` can continue an unquoted identifier after the first
+    /// character.
+    fn is_identifier_part(&self, ch: char) -> bool {
+        ch.is_alphanumeric() || ch == '_'
+    }
+
+    /// The character used to quote identifiers containing characters that
+    /// would otherwise be invalid, e.g. `"weird column"` or `` `weird column` ``.
+    fn quote_char(&self) -> char {
+        '"'
+    }
+}
+
+/// The ANSI-ish dialect Poubelle has always spoken: double-quoted
+/// identifiers, case-insensitive keywords.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GenericDialect;
+
+impl Dialect for GenericDialect {
+    fn name(&self) -> &'static str {
+        "generic"
+    }
+
+    fn keyword(&self, uppercased: &str) -> Option<Token> {
+        generic_keyword(uppercased)
+    }
+}
+
+/// MySQL-flavored identifier rules: backtick-quoted identifiers and `$` as a
+/// valid identifier character.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MySqlDialect;
+
+impl Dialect for MySqlDialect {
+    fn name(&self) -> &'static str {
+        "mysql"
+    }
+
+    fn keyword(&self, uppercased: &str) -> Option<Token> {
+        generic_keyword(uppercased)
+    }
+
+    fn is_identifier_part(&self, ch: char) -> bool {
+        ch.is_alphanumeric() || ch == '_' || ch == '$'
+    }
+
+    fn quote_char(&self) -> char {
+        '`'
+    }
+}
+
+fn generic_keyword(uppercased: &str) -> Option<Token> {
+    Some(match uppercased {
+        "SELECT" => Token::Select,
+        "FROM" => Token::From,
+        "INSERT" => Token::Insert,
+        "INTO" => Token::Into,
+        "VALUES" => Token::Values,
+        "CREATE" => Token::Create,
+        "TABLE" => Token::Table,
+        "INT" => Token::Int,
+        "TEXT" => Token::Text,
+        "FLOAT" => Token::Float,
+        "REAL" => Token::Float,
+        "BOOL" => Token::Bool,
+        "BOOLEAN" => Token::Bool,
+        "TIMESTAMP" => Token::Timestamp,
+        "NULL" => Token::Null,
+        "TRUE" => Token::True,
+        "FALSE" => Token::False,
+        "WHERE" => Token::Where,
+        "LIMIT" => Token::Limit,
+        "FORMAT" => Token::Format,
+        "JSON" => Token::Json,
+        "AS" => Token::As,
+        "AND" => Token::And,
+        "OR" => Token::Or,
+        "NOT" => Token::Not,
+        "DISTINCT" => Token::Distinct,
+        "GROUP" => Token::Group,
+        "BY" => Token::By,
+        "ORDER" => Token::Order,
+        "ASC" => Token::Asc,
+        "DESC" => Token::Desc,
+        "COUNT" => Token::Count,
+        "SUM" => Token::Sum,
+        "AVG" => Token::Avg,
+        "MIN" => Token::Min,
+        "MAX" => Token::Max,
+        "DROP" => Token::Drop,
+        "BEGIN" => Token::Begin,
+        "COMMIT" => Token::Commit,
+        "ROLLBACK" => Token::Rollback,
+        "SAVEPOINT" => Token::Savepoint,
+        "RELEASE" => Token::Release,
+        "TO" => Token::To,
+        "TRANSACTION" => Token::Transaction,
+        "VERSIONED" => Token::Versioned,
+        "KEY" => Token::Key,
+        "WRITER" => Token::Writer,
+        _ => return None,
+    })
+}
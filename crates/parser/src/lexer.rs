@@ -1,3 +1,40 @@
+use crate::dialect::{Dialect, GenericDialect};
+
+/// A byte-offset range (`start` inclusive, `end` exclusive) into the source
+/// text a token was lexed from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// A sentinel for a token that has no real position in any source text
+    /// (e.g. one synthesized rather than lexed), so code that needs to
+    /// construct a [`TokenWithSpan`] without having actually scanned it
+    /// still has a span to hand over.
+    pub fn empty() -> Self {
+        Self { start: 0, end: 0 }
+    }
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "byte {}..{}", self.start, self.end)
+    }
+}
+
+/// A [`Token`] paired with the span of source bytes it was lexed from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenWithSpan {
+    pub token: Token,
+    pub span: Span,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     Select,
@@ -10,18 +47,55 @@ pub enum Token {
     Table,
     Int,
     Text,
+    Float,
+    Bool,
+    Timestamp,
     Null,
+    True,
+    False,
     Where,
     Limit,
     Format,
     Json,
+    As,
+    And,
+    Or,
+    Not,
+    Distinct,
+    Group,
+    By,
+    Order,
+    Asc,
+    Desc,
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+    Begin,
+    Commit,
+    Rollback,
+    Savepoint,
+    Release,
+    To,
+    Transaction,
+    Versioned,
+    Key,
+    Writer,
     Ident(String),
     Number(i64),
+    FloatNumber(f64),
     String(String),
+    /// A positional bind parameter, e.g. `$1`.
+    Placeholder(usize),
     Asterisk,
     Comma,
     LeftParen,
     RightParen,
+    Plus,
+    Minus,
+    Slash,
+    Semicolon,
     Equal,
     NotEqual,
     LessThan,
@@ -34,13 +108,38 @@ pub enum Token {
 pub struct Lexer {
     input: Vec<char>,
     pos: usize,
+    byte_pos: usize,
+    dialect: Box<dyn Dialect>,
+    /// How many `?` placeholders have been lexed so far, so each one gets
+    /// the next 1-based positional number in occurrence order — unlike
+    /// `$N`, a `?` doesn't carry its own number in the source text.
+    question_placeholders: usize,
 }
 
 impl Lexer {
     pub fn new(input: &str) -> Self {
+        Self::with_dialect(input, Box::new(GenericDialect))
+    }
+
+    pub fn with_dialect(input: &str, dialect: Box<dyn Dialect>) -> Self {
         Self {
             input: input.chars().collect(),
             pos: 0,
+            byte_pos: 0,
+            dialect,
+            question_placeholders: 0,
+        }
+    }
+
+    /// Read the next token along with the span of source bytes it came from.
+    pub fn next_token_with_span(&mut self) -> TokenWithSpan {
+        self.skip_whitespace();
+        let start = self.byte_pos;
+        let token = self.next_token();
+        let end = self.byte_pos;
+        TokenWithSpan {
+            token,
+            span: Span::new(start, end),
         }
     }
 
@@ -55,119 +154,192 @@ impl Lexer {
 
         match ch {
             '*' => {
-                self.pos += 1;
+                self.advance();
                 Token::Asterisk
             }
             ',' => {
-                self.pos += 1;
+                self.advance();
                 Token::Comma
             }
             '(' => {
-                self.pos += 1;
+                self.advance();
                 Token::LeftParen
             }
             ')' => {
-                self.pos += 1;
+                self.advance();
                 Token::RightParen
             }
+            '+' => {
+                self.advance();
+                Token::Plus
+            }
+            '/' => {
+                self.advance();
+                Token::Slash
+            }
+            ';' => {
+                self.advance();
+                Token::Semicolon
+            }
             '=' => {
-                self.pos += 1;
+                self.advance();
                 Token::Equal
             }
             '!' => {
-                self.pos += 1;
+                self.advance();
                 if self.pos < self.input.len() && self.input[self.pos] == '=' {
-                    self.pos += 1;
+                    self.advance();
                     Token::NotEqual
                 } else {
                     self.next_token()
                 }
             }
             '<' => {
-                self.pos += 1;
+                self.advance();
                 if self.pos < self.input.len() && self.input[self.pos] == '=' {
-                    self.pos += 1;
+                    self.advance();
                     Token::LessThanOrEqual
                 } else {
                     Token::LessThan
                 }
             }
             '>' => {
-                self.pos += 1;
+                self.advance();
                 if self.pos < self.input.len() && self.input[self.pos] == '=' {
-                    self.pos += 1;
+                    self.advance();
                     Token::GreaterThanOrEqual
                 } else {
                     Token::GreaterThan
                 }
             }
             '\'' => self.read_string(),
-            '0'..='9' | '-' => self.read_number(),
-            _ if ch.is_alphabetic() => self.read_identifier(),
+            '$' => self.read_placeholder(),
+            '?' => self.read_question_placeholder(),
+            '-' if self.peek_is_digit() => self.read_number(),
+            '-' => {
+                self.advance();
+                Token::Minus
+            }
+            '0'..='9' => self.read_number(),
+            _ if ch == self.dialect.quote_char() => self.read_quoted_identifier(),
+            _ if self.dialect.is_identifier_start(ch) => self.read_identifier(),
             _ => {
-                self.pos += 1;
+                self.advance();
                 self.next_token()
             }
         }
     }
 
+    fn peek_is_digit(&self) -> bool {
+        self.input
+            .get(self.pos + 1)
+            .is_some_and(|c| c.is_ascii_digit())
+    }
+
+    fn advance(&mut self) {
+        self.byte_pos += self.input[self.pos].len_utf8();
+        self.pos += 1;
+    }
+
     fn skip_whitespace(&mut self) {
         while self.pos < self.input.len() && self.input[self.pos].is_whitespace() {
-            self.pos += 1;
+            self.advance();
         }
     }
 
     fn read_identifier(&mut self) -> Token {
         let start = self.pos;
-        while self.pos < self.input.len()
-            && (self.input[self.pos].is_alphanumeric() || self.input[self.pos] == '_')
-        {
-            self.pos += 1;
+        while self.pos < self.input.len() && self.dialect.is_identifier_part(self.input[self.pos]) {
+            self.advance();
         }
 
         let ident: String = self.input[start..self.pos].iter().collect();
-        match ident.to_uppercase().as_str() {
-            "SELECT" => Token::Select,
-            "FROM" => Token::From,
-            "INSERT" => Token::Insert,
-            "INTO" => Token::Into,
-            "VALUES" => Token::Values,
-            "CREATE" => Token::Create,
-            "TABLE" => Token::Table,
-            "INT" => Token::Int,
-            "TEXT" => Token::Text,
-            "NULL" => Token::Null,
-            "WHERE" => Token::Where,
-            "LIMIT" => Token::Limit,
-            "FORMAT" => Token::Format,
-            "JSON" => Token::Json,
-            "DROP" => Token::Drop,
-            _ => Token::Ident(ident),
+        self.dialect
+            .keyword(ident.to_uppercase().as_str())
+            .unwrap_or(Token::Ident(ident))
+    }
+
+    /// Read a quoted identifier, e.g. `"weird column"` or `` `weird column` ``
+    /// depending on the dialect. Quoted identifiers are never treated as
+    /// keywords.
+    fn read_quoted_identifier(&mut self) -> Token {
+        let quote = self.dialect.quote_char();
+        self.advance();
+        let start = self.pos;
+        while self.pos < self.input.len() && self.input[self.pos] != quote {
+            self.advance();
         }
+
+        let ident: String = self.input[start..self.pos].iter().collect();
+        if self.pos < self.input.len() {
+            self.advance();
+        }
+        Token::Ident(ident)
     }
 
     fn read_number(&mut self) -> Token {
         let start = self.pos;
         if self.input[self.pos] == '-' {
-            self.pos += 1;
+            self.advance();
         }
         while self.pos < self.input.len() && self.input[self.pos].is_numeric() {
-            self.pos += 1;
+            self.advance();
+        }
+
+        let mut is_float = false;
+        if self.pos < self.input.len()
+            && self.input[self.pos] == '.'
+            && self
+                .input
+                .get(self.pos + 1)
+                .is_some_and(|c| c.is_ascii_digit())
+        {
+            is_float = true;
+            self.advance();
+            while self.pos < self.input.len() && self.input[self.pos].is_numeric() {
+                self.advance();
+            }
         }
 
         let num_str: String = self.input[start..self.pos].iter().collect();
-        Token::Number(num_str.parse().unwrap_or(0))
+        if is_float {
+            Token::FloatNumber(num_str.parse().unwrap_or(0.0))
+        } else {
+            Token::Number(num_str.parse().unwrap_or(0))
+        }
+    }
+
+    fn read_placeholder(&mut self) -> Token {
+        self.advance();
+        let start = self.pos;
+        while self.pos < self.input.len() && self.input[self.pos].is_ascii_digit() {
+            self.advance();
+        }
+
+        let digits: String = self.input[start..self.pos].iter().collect();
+        Token::Placeholder(digits.parse().unwrap_or(0))
+    }
+
+    /// Read a bare `?` placeholder, numbered by occurrence order (1-based,
+    /// matching `$1`'s convention) since, unlike `$N`, it carries no number
+    /// of its own in the source text.
+    fn read_question_placeholder(&mut self) -> Token {
+        self.advance();
+        self.question_placeholders += 1;
+        Token::Placeholder(self.question_placeholders)
     }
 
     fn read_string(&mut self) -> Token {
-        self.pos += 1;
+        self.advance();
         let start = self.pos;
         while self.pos < self.input.len() && self.input[self.pos] != '\'' {
-            self.pos += 1;
+            self.advance();
         }
 
         let s: String = self.input[start..self.pos].iter().collect();
-        self.pos += 1;
+        if self.pos < self.input.len() {
+            self.advance();
+        }
         Token::String(s)
     }
 }
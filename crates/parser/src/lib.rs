@@ -1,9 +1,15 @@
 mod ast;
+mod dialect;
 mod lexer;
+mod params;
 mod parser;
 
 pub use ast::{
-    ArithmeticOp, Column, CompareOp, CreateTable, Expr, InsertStatement, OutputFormat,
-    SelectExprQuery, SelectItem, SelectQuery, Statement, WhereClause,
+    AggregateFunc, ArithmeticOp, Column, CompareOp, CreateTable, DropTable, Expr, InsertStatement,
+    OutputFormat, SelectExprQuery, SelectItem, SelectQuery, SortDir, Statement, VersionedInsert,
+    WhereClause, WhereExpr,
 };
+pub use dialect::{Dialect, GenericDialect, MySqlDialect};
+pub use lexer::{Span, TokenWithSpan};
+pub use params::{bind_params, BindError};
 pub use parser::{ParseError, Parser};
@@ -0,0 +1,113 @@
+use crate::ast::{Expr, InsertStatement, SelectExprQuery, SelectItem, SelectQuery, Statement};
+use crate::ast::{WhereClause, WhereExpr};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum BindError {
+    #[error("No value supplied for parameter ${0}")]
+    MissingParameter(usize),
+}
+
+/// Substitute the positional bind parameters (`$1`, `$2`, ...) in `statement`
+/// with the literal expressions in `params`, returning a statement with no
+/// remaining `Expr::Placeholder` nodes.
+///
+/// `params[0]` is bound to `$1`, `params[1]` to `$2`, and so on.
+pub fn bind_params(statement: Statement, params: &[Expr]) -> Result<Statement, BindError> {
+    match statement {
+        Statement::Select(query) => Ok(Statement::Select(bind_select(query, params)?)),
+        Statement::SelectExpr(query) => Ok(Statement::SelectExpr(bind_select_expr(query, params)?)),
+        Statement::Insert(insert) => Ok(Statement::Insert(bind_insert(insert, params)?)),
+        other => Ok(other),
+    }
+}
+
+fn bind_select(mut query: SelectQuery, params: &[Expr]) -> Result<SelectQuery, BindError> {
+    query.where_clause = query
+        .where_clause
+        .map(|where_expr| bind_where(where_expr, params))
+        .transpose()?;
+    query.projection = query
+        .projection
+        .into_iter()
+        .map(|item| {
+            Ok(SelectItem {
+                expr: bind_expr(item.expr, params)?,
+                alias: item.alias,
+            })
+        })
+        .collect::<Result<_, BindError>>()?;
+    Ok(query)
+}
+
+fn bind_select_expr(
+    mut query: SelectExprQuery,
+    params: &[Expr],
+) -> Result<SelectExprQuery, BindError> {
+    query.expressions = query
+        .expressions
+        .into_iter()
+        .map(|item| {
+            Ok(SelectItem {
+                expr: bind_expr(item.expr, params)?,
+                alias: item.alias,
+            })
+        })
+        .collect::<Result<_, BindError>>()?;
+    Ok(query)
+}
+
+fn bind_insert(mut insert: InsertStatement, params: &[Expr]) -> Result<InsertStatement, BindError> {
+    insert.values = insert
+        .values
+        .into_iter()
+        .map(|expr| bind_expr(expr, params))
+        .collect::<Result<_, BindError>>()?;
+    Ok(insert)
+}
+
+fn bind_where(where_expr: WhereExpr, params: &[Expr]) -> Result<WhereExpr, BindError> {
+    match where_expr {
+        WhereExpr::Comparison(clause) => Ok(WhereExpr::Comparison(WhereClause {
+            left: bind_expr(clause.left, params)?,
+            operator: clause.operator,
+            right: bind_expr(clause.right, params)?,
+        })),
+        WhereExpr::And(left, right) => Ok(WhereExpr::And(
+            Box::new(bind_where(*left, params)?),
+            Box::new(bind_where(*right, params)?),
+        )),
+        WhereExpr::Or(left, right) => Ok(WhereExpr::Or(
+            Box::new(bind_where(*left, params)?),
+            Box::new(bind_where(*right, params)?),
+        )),
+        WhereExpr::Not(inner) => Ok(WhereExpr::Not(Box::new(bind_where(*inner, params)?))),
+    }
+}
+
+fn bind_expr(expr: Expr, params: &[Expr]) -> Result<Expr, BindError> {
+    match expr {
+        // `n.checked_sub(1)` rather than `n - 1`: placeholders are 1-based
+        // (`$1` is the first param), but the lexer doesn't reject `$0`, and
+        // `0usize - 1` would panic on arithmetic overflow instead of
+        // reporting it as the missing-parameter error it actually is.
+        Expr::Placeholder(n) => n
+            .checked_sub(1)
+            .and_then(|index| params.get(index))
+            .cloned()
+            .ok_or(BindError::MissingParameter(n)),
+        Expr::BinaryOp { left, op, right } => Ok(Expr::BinaryOp {
+            left: Box::new(bind_expr(*left, params)?),
+            op,
+            right: Box::new(bind_expr(*right, params)?),
+        }),
+        Expr::Aggregate { func, arg } => Ok(Expr::Aggregate {
+            func,
+            arg: arg
+                .map(|a| bind_expr(*a, params))
+                .transpose()?
+                .map(Box::new),
+        }),
+        other => Ok(other),
+    }
+}
@@ -1,28 +1,47 @@
 use crate::ast::{
-    ArithmeticOp, Column, CompareOp, CreateTable, DropTable, Expr, InsertStatement, OutputFormat,
-    SelectExprQuery, SelectItem, SelectQuery, Statement, WhereClause,
+    AggregateFunc, ArithmeticOp, Column, CompareOp, CreateTable, DropTable, Expr, InsertStatement,
+    OutputFormat, SelectExprQuery, SelectItem, SelectQuery, SortDir, Statement, VersionedInsert,
+    WhereClause, WhereExpr,
 };
-use crate::lexer::{Lexer, Token};
+use crate::dialect::Dialect;
+use crate::lexer::{Lexer, Span, Token, TokenWithSpan};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum ParseError {
-    #[error("Unexpected token: {0:?}")]
-    UnexpectedToken(Token),
-    #[error("Expected token: {0}")]
-    ExpectedToken(String),
+    #[error("Unexpected token at {1}: {0:?}")]
+    UnexpectedToken(Token, Span),
+    #[error("Expected token at {1}: {0}")]
+    ExpectedToken(String, Span),
 }
 
 pub struct Parser {
     lexer: Lexer,
     current: Token,
+    current_span: Span,
 }
 
 impl Parser {
     pub fn new(input: &str) -> Self {
-        let mut lexer = Lexer::new(input);
-        let current = lexer.next_token();
-        Self { lexer, current }
+        Self::from_lexer(Lexer::new(input))
+    }
+
+    /// Create a parser that tokenizes `input` according to `dialect` instead
+    /// of the default [`crate::dialect::GenericDialect`].
+    pub fn with_dialect(input: &str, dialect: Box<dyn Dialect>) -> Self {
+        Self::from_lexer(Lexer::with_dialect(input, dialect))
+    }
+
+    fn from_lexer(mut lexer: Lexer) -> Self {
+        let TokenWithSpan {
+            token: current,
+            span: current_span,
+        } = lexer.next_token_with_span();
+        Self {
+            lexer,
+            current,
+            current_span,
+        }
     }
 
     pub fn parse(&mut self) -> Result<Statement, ParseError> {
@@ -31,12 +50,35 @@ impl Parser {
             Token::Insert => self.parse_insert(),
             Token::Create => self.parse_create(),
             Token::Drop => self.parse_drop(),
-            tok => Err(ParseError::UnexpectedToken(tok.clone())),
+            Token::Begin => self.parse_begin(),
+            Token::Commit => self.parse_commit(),
+            Token::Rollback => self.parse_rollback(),
+            Token::Savepoint => self.parse_savepoint(),
+            Token::Release => self.parse_release(),
+            tok => Err(ParseError::UnexpectedToken(tok.clone(), self.current_span)),
         }
     }
 
+    /// Parse a `;`-separated list of statements, e.g. a client sending
+    /// several inserts in one round trip. A trailing `;` is optional.
+    pub fn parse_batch(&mut self) -> Result<Vec<Statement>, ParseError> {
+        let mut statements = Vec::new();
+
+        while self.current != Token::Eof {
+            statements.push(self.parse()?);
+
+            if self.current == Token::Semicolon {
+                self.advance();
+            }
+        }
+
+        Ok(statements)
+    }
+
     fn advance(&mut self) {
-        self.current = self.lexer.next_token();
+        let TokenWithSpan { token, span } = self.lexer.next_token_with_span();
+        self.current = token;
+        self.current_span = span;
     }
 
     fn expect(&mut self, expected: Token) -> Result<(), ParseError> {
@@ -44,13 +86,23 @@ impl Parser {
             self.advance();
             Ok(())
         } else {
-            Err(ParseError::ExpectedToken(format!("{:?}", expected)))
+            Err(ParseError::ExpectedToken(
+                format!("{:?}", expected),
+                self.current_span,
+            ))
         }
     }
 
     fn parse_select(&mut self) -> Result<Statement, ParseError> {
         self.expect(Token::Select)?;
 
+        let distinct = if self.current == Token::Distinct {
+            self.advance();
+            true
+        } else {
+            false
+        };
+
         // Check if this is a simple column SELECT (with FROM) or an expression SELECT
         // Try parsing as expressions first
         let mut items = Vec::new();
@@ -60,7 +112,7 @@ impl Parser {
             self.advance();
             // This must be a table SELECT
             self.expect(Token::From)?;
-            return self.parse_table_select(vec!["*".to_string()]);
+            return self.parse_table_select(vec!["*".to_string()], Vec::new(), distinct);
         }
 
         // Parse expression list
@@ -75,7 +127,10 @@ impl Parser {
                     self.advance();
                     Some(name)
                 } else {
-                    return Err(ParseError::ExpectedToken("alias name".to_string()));
+                    return Err(ParseError::ExpectedToken(
+                        "alias name".to_string(),
+                        self.current_span,
+                    ));
                 }
             } else {
                 None
@@ -94,13 +149,13 @@ impl Parser {
             self.advance();
             // This is a table select - extract column names from expressions
             let columns: Vec<String> = items
-                .into_iter()
-                .map(|item| match item.expr {
-                    Expr::Column(name) => name,
+                .iter()
+                .map(|item| match &item.expr {
+                    Expr::Column(name) => name.clone(),
                     _ => "?column?".to_string(), // Fallback for complex expressions in table selects
                 })
                 .collect();
-            return self.parse_table_select(columns);
+            return self.parse_table_select(columns, items, distinct);
         }
 
         // No FROM clause - this is an expression-only SELECT
@@ -117,13 +172,21 @@ impl Parser {
         }))
     }
 
-    fn parse_table_select(&mut self, columns: Vec<String>) -> Result<Statement, ParseError> {
+    fn parse_table_select(
+        &mut self,
+        columns: Vec<String>,
+        projection: Vec<SelectItem>,
+        distinct: bool,
+    ) -> Result<Statement, ParseError> {
         let table = if let Token::Ident(name) = &self.current {
             let name = name.clone();
             self.advance();
             name
         } else {
-            return Err(ParseError::ExpectedToken("table name".to_string()));
+            return Err(ParseError::ExpectedToken(
+                "table name".to_string(),
+                self.current_span,
+            ));
         };
 
         let where_clause = if self.current == Token::Where {
@@ -133,13 +196,63 @@ impl Parser {
             None
         };
 
+        let group_by = if self.current == Token::Group {
+            self.advance();
+            self.expect(Token::By)?;
+
+            let mut cols = Vec::new();
+            loop {
+                cols.push(self.expect_ident("column name")?);
+                if self.current != Token::Comma {
+                    break;
+                }
+                self.advance();
+            }
+            cols
+        } else {
+            Vec::new()
+        };
+
+        let order_by = if self.current == Token::Order {
+            self.advance();
+            self.expect(Token::By)?;
+
+            let mut cols = Vec::new();
+            loop {
+                let column = self.expect_ident("column name")?;
+                let dir = match self.current {
+                    Token::Asc => {
+                        self.advance();
+                        SortDir::Asc
+                    }
+                    Token::Desc => {
+                        self.advance();
+                        SortDir::Desc
+                    }
+                    _ => SortDir::Asc,
+                };
+                cols.push((column, dir));
+
+                if self.current != Token::Comma {
+                    break;
+                }
+                self.advance();
+            }
+            cols
+        } else {
+            Vec::new()
+        };
+
         let limit = if self.current == Token::Limit {
             self.advance();
             if let Token::Number(n) = self.current {
                 self.advance();
                 Some(n as usize)
             } else {
-                return Err(ParseError::ExpectedToken("number".to_string()));
+                return Err(ParseError::ExpectedToken(
+                    "number".to_string(),
+                    self.current_span,
+                ));
             }
         } else {
             None
@@ -149,8 +262,12 @@ impl Parser {
 
         Ok(Statement::Select(SelectQuery {
             columns,
+            projection,
             table,
             where_clause,
+            group_by,
+            order_by,
+            distinct,
             limit,
             format,
         }))
@@ -163,7 +280,10 @@ impl Parser {
                 self.advance();
                 Ok(OutputFormat::Json)
             } else {
-                Err(ParseError::ExpectedToken("JSON".to_string()))
+                Err(ParseError::ExpectedToken(
+                    "JSON".to_string(),
+                    self.current_span,
+                ))
             }
         } else {
             Ok(OutputFormat::Debug)
@@ -232,6 +352,19 @@ impl Parser {
                 self.advance();
                 Ok(Expr::Text(s))
             }
+            Token::FloatNumber(f) => {
+                let f = *f;
+                self.advance();
+                Ok(Expr::Float(f))
+            }
+            Token::True => {
+                self.advance();
+                Ok(Expr::Bool(true))
+            }
+            Token::False => {
+                self.advance();
+                Ok(Expr::Bool(false))
+            }
             Token::Null => {
                 self.advance();
                 Ok(Expr::Null)
@@ -241,13 +374,39 @@ impl Parser {
                 self.advance();
                 Ok(Expr::Column(name))
             }
+            Token::Placeholder(n) => {
+                let n = *n;
+                self.advance();
+                Ok(Expr::Placeholder(n))
+            }
+            Token::Count | Token::Sum | Token::Avg | Token::Min | Token::Max => {
+                let func = match &self.current {
+                    Token::Count => AggregateFunc::Count,
+                    Token::Sum => AggregateFunc::Sum,
+                    Token::Avg => AggregateFunc::Avg,
+                    Token::Min => AggregateFunc::Min,
+                    _ => AggregateFunc::Max,
+                };
+                self.advance();
+                self.expect(Token::LeftParen)?;
+
+                let arg = if func == AggregateFunc::Count && self.current == Token::Asterisk {
+                    self.advance();
+                    None
+                } else {
+                    Some(Box::new(self.parse_expression()?))
+                };
+
+                self.expect(Token::RightParen)?;
+                Ok(Expr::Aggregate { func, arg })
+            }
             Token::LeftParen => {
                 self.advance();
                 let expr = self.parse_expression()?;
                 self.expect(Token::RightParen)?;
                 Ok(expr)
             }
-            tok => Err(ParseError::UnexpectedToken(tok.clone())),
+            tok => Err(ParseError::UnexpectedToken(tok.clone(), self.current_span)),
         }
     }
 
@@ -260,7 +419,10 @@ impl Parser {
             self.advance();
             name
         } else {
-            return Err(ParseError::ExpectedToken("table name".to_string()));
+            return Err(ParseError::ExpectedToken(
+                "table name".to_string(),
+                self.current_span,
+            ));
         };
 
         self.expect(Token::LeftParen)?;
@@ -271,7 +433,10 @@ impl Parser {
                 columns.push(name.clone());
                 self.advance();
             } else {
-                return Err(ParseError::ExpectedToken("column name".to_string()));
+                return Err(ParseError::ExpectedToken(
+                    "column name".to_string(),
+                    self.current_span,
+                ));
             }
 
             if self.current != Token::Comma {
@@ -297,11 +462,34 @@ impl Parser {
                     self.advance();
                     v
                 }
+                Token::FloatNumber(f) => {
+                    let v = Expr::Float(*f);
+                    self.advance();
+                    v
+                }
+                Token::True => {
+                    self.advance();
+                    Expr::Bool(true)
+                }
+                Token::False => {
+                    self.advance();
+                    Expr::Bool(false)
+                }
                 Token::Null => {
                     self.advance();
                     Expr::Null
                 }
-                _ => return Err(ParseError::ExpectedToken("value".to_string())),
+                Token::Placeholder(n) => {
+                    let v = Expr::Placeholder(*n);
+                    self.advance();
+                    v
+                }
+                _ => {
+                    return Err(ParseError::ExpectedToken(
+                        "value".to_string(),
+                        self.current_span,
+                    ))
+                }
             };
             values.push(value);
 
@@ -313,13 +501,40 @@ impl Parser {
 
         self.expect(Token::RightParen)?;
 
+        let versioned = if self.current == Token::Versioned {
+            self.advance();
+            self.expect(Token::Key)?;
+            let key = self.expect_string("key")?;
+            self.expect(Token::Writer)?;
+            let writer = self.expect_string("writer")?;
+            Some(VersionedInsert { key, writer })
+        } else {
+            None
+        };
+
         Ok(Statement::Insert(InsertStatement {
             table,
             columns,
             values,
+            versioned,
         }))
     }
 
+    /// Consume the current token as a string literal for `what`, e.g. the
+    /// key/writer names an `INSERT ... VERSIONED` clause takes.
+    fn expect_string(&mut self, what: &str) -> Result<String, ParseError> {
+        if let Token::String(s) = &self.current {
+            let s = s.clone();
+            self.advance();
+            Ok(s)
+        } else {
+            Err(ParseError::ExpectedToken(
+                format!("{} string", what),
+                self.current_span,
+            ))
+        }
+    }
+
     fn parse_create(&mut self) -> Result<Statement, ParseError> {
         self.expect(Token::Create)?;
         self.expect(Token::Table)?;
@@ -329,7 +544,10 @@ impl Parser {
             self.advance();
             n
         } else {
-            return Err(ParseError::ExpectedToken("table name".to_string()));
+            return Err(ParseError::ExpectedToken(
+                "table name".to_string(),
+                self.current_span,
+            ));
         };
 
         self.expect(Token::LeftParen)?;
@@ -341,7 +559,10 @@ impl Parser {
                 self.advance();
                 n
             } else {
-                return Err(ParseError::ExpectedToken("column name".to_string()));
+                return Err(ParseError::ExpectedToken(
+                    "column name".to_string(),
+                    self.current_span,
+                ));
             };
 
             let col_type = match &self.current {
@@ -353,7 +574,24 @@ impl Parser {
                     self.advance();
                     "TEXT".to_string()
                 }
-                _ => return Err(ParseError::ExpectedToken("column type".to_string())),
+                Token::Float => {
+                    self.advance();
+                    "FLOAT".to_string()
+                }
+                Token::Bool => {
+                    self.advance();
+                    "BOOL".to_string()
+                }
+                Token::Timestamp => {
+                    self.advance();
+                    "TIMESTAMP".to_string()
+                }
+                _ => {
+                    return Err(ParseError::ExpectedToken(
+                        "column type".to_string(),
+                        self.current_span,
+                    ))
+                }
             };
 
             columns.push(Column {
@@ -372,14 +610,59 @@ impl Parser {
         Ok(Statement::Create(CreateTable { name, columns }))
     }
 
-    fn parse_where(&mut self) -> Result<WhereClause, ParseError> {
-        let column = if let Token::Ident(name) = &self.current {
-            let name = name.clone();
+    /// Parse a full boolean `WHERE` expression, handling `OR` (lowest precedence),
+    /// `AND`, `NOT`, and parenthesized groups.
+    fn parse_where(&mut self) -> Result<WhereExpr, ParseError> {
+        self.parse_where_or()
+    }
+
+    fn parse_where_or(&mut self) -> Result<WhereExpr, ParseError> {
+        let mut left = self.parse_where_and()?;
+
+        while self.current == Token::Or {
             self.advance();
-            name
-        } else {
-            return Err(ParseError::ExpectedToken("column name".to_string()));
-        };
+            let right = self.parse_where_and()?;
+            left = WhereExpr::Or(Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn parse_where_and(&mut self) -> Result<WhereExpr, ParseError> {
+        let mut left = self.parse_where_not()?;
+
+        while self.current == Token::And {
+            self.advance();
+            let right = self.parse_where_not()?;
+            left = WhereExpr::And(Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn parse_where_not(&mut self) -> Result<WhereExpr, ParseError> {
+        if self.current == Token::Not {
+            self.advance();
+            let inner = self.parse_where_not()?;
+            return Ok(WhereExpr::Not(Box::new(inner)));
+        }
+
+        self.parse_where_primary()
+    }
+
+    fn parse_where_primary(&mut self) -> Result<WhereExpr, ParseError> {
+        if self.current == Token::LeftParen {
+            self.advance();
+            let inner = self.parse_where_or()?;
+            self.expect(Token::RightParen)?;
+            return Ok(inner);
+        }
+
+        Ok(WhereExpr::Comparison(self.parse_where_comparison()?))
+    }
+
+    fn parse_where_comparison(&mut self) -> Result<WhereClause, ParseError> {
+        let left = self.parse_expression()?;
 
         let operator = match &self.current {
             Token::Equal => CompareOp::Equal,
@@ -388,32 +671,21 @@ impl Parser {
             Token::LessThanOrEqual => CompareOp::LessThanOrEqual,
             Token::GreaterThan => CompareOp::GreaterThan,
             Token::GreaterThanOrEqual => CompareOp::GreaterThanOrEqual,
-            _ => return Err(ParseError::ExpectedToken("comparison operator".to_string())),
+            _ => {
+                return Err(ParseError::ExpectedToken(
+                    "comparison operator".to_string(),
+                    self.current_span,
+                ))
+            }
         };
         self.advance();
 
-        let value = match &self.current {
-            Token::Number(n) => {
-                let v = Expr::Int(*n);
-                self.advance();
-                v
-            }
-            Token::String(s) => {
-                let v = Expr::Text(s.clone());
-                self.advance();
-                v
-            }
-            Token::Null => {
-                self.advance();
-                Expr::Null
-            }
-            _ => return Err(ParseError::ExpectedToken("value".to_string())),
-        };
+        let right = self.parse_expression()?;
 
         Ok(WhereClause {
-            column,
+            left,
             operator,
-            value,
+            right,
         })
     }
 
@@ -423,10 +695,73 @@ impl Parser {
 
         let name = match &self.current {
             Token::Ident(s) => s.clone(),
-            _ => return Err(ParseError::ExpectedToken("table name".to_string())),
+            _ => {
+                return Err(ParseError::ExpectedToken(
+                    "table name".to_string(),
+                    self.current_span,
+                ))
+            }
         };
         self.advance();
 
         Ok(Statement::Drop(DropTable { name }))
     }
+
+    fn parse_begin(&mut self) -> Result<Statement, ParseError> {
+        self.advance();
+        if self.current == Token::Transaction {
+            self.advance();
+        }
+        Ok(Statement::Begin)
+    }
+
+    fn parse_commit(&mut self) -> Result<Statement, ParseError> {
+        self.advance();
+        Ok(Statement::Commit)
+    }
+
+    fn parse_rollback(&mut self) -> Result<Statement, ParseError> {
+        self.advance();
+
+        if self.current != Token::To {
+            return Ok(Statement::Rollback(None));
+        }
+        self.advance();
+
+        if self.current == Token::Savepoint {
+            self.advance();
+        }
+
+        let name = self.expect_ident("savepoint name")?;
+        Ok(Statement::Rollback(Some(name)))
+    }
+
+    fn parse_savepoint(&mut self) -> Result<Statement, ParseError> {
+        self.advance();
+        let name = self.expect_ident("savepoint name")?;
+        Ok(Statement::Savepoint(name))
+    }
+
+    fn parse_release(&mut self) -> Result<Statement, ParseError> {
+        self.advance();
+        if self.current == Token::Savepoint {
+            self.advance();
+        }
+        let name = self.expect_ident("savepoint name")?;
+        Ok(Statement::ReleaseSavepoint(name))
+    }
+
+    fn expect_ident(&mut self, what: &str) -> Result<String, ParseError> {
+        match &self.current {
+            Token::Ident(name) => {
+                let name = name.clone();
+                self.advance();
+                Ok(name)
+            }
+            _ => Err(ParseError::ExpectedToken(
+                what.to_string(),
+                self.current_span,
+            )),
+        }
+    }
 }
@@ -1,29 +1,89 @@
-use crate::error::{Error, Result};
+use crate::error::{Error, Result, SqlState};
+use crate::protocol;
 use crate::types::{Row, Value};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpStream;
+use rand::Rng;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpStream, UnixStream};
+
+/// The default bound on how long [`PoubelleClient::connect_with_retry`]
+/// keeps retrying a transient failure before giving up.
+const DEFAULT_MAX_ELAPSED: Duration = Duration::from_secs(30);
+
+/// The shape of the exponential backoff [`PoubelleClient::connect_with_retry`]
+/// waits between attempts. The overall time budget isn't part of this —
+/// that's [`PoubelleClient::with_max_elapsed`], since it's a property of the
+/// connection, not of one retry attempt's pacing.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn with_initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    pub fn with_max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+}
+
+/// Where [`PoubelleClient::connect`] dials: a TCP host/port pair, or a
+/// filesystem path to a Unix domain socket for local deployments. Parsed
+/// out of the connection string's host component the same way postgres
+/// URLs distinguish a socket directory from a TCP host — a leading `/`
+/// (after percent-decoding) means Unix.
+#[derive(Debug, Clone)]
+enum ConnectTarget {
+    Tcp { host: String, port: u16 },
+    Unix(PathBuf),
+}
 
 pub struct PoubelleClient {
-    stream: Option<TcpStream>,
-    host: String,
-    port: u16,
+    reader: Option<Box<dyn AsyncRead + Unpin + Send>>,
+    writer: Option<Box<dyn AsyncWrite + Unpin + Send>>,
+    target: ConnectTarget,
     username: String,
     password: String,
+    max_elapsed: Duration,
 }
 
 impl PoubelleClient {
     pub fn new(connection_string: &str) -> Result<Self> {
-        let parsed = Self::parse_connection_string(connection_string)?;
+        let (target, username, password) = Self::parse_connection_string(connection_string)?;
         Ok(Self {
-            stream: None,
-            host: parsed.0,
-            port: parsed.1,
-            username: parsed.2,
-            password: parsed.3,
+            reader: None,
+            writer: None,
+            target,
+            username,
+            password,
+            max_elapsed: DEFAULT_MAX_ELAPSED,
         })
     }
 
-    fn parse_connection_string(conn_str: &str) -> Result<(String, u16, String, String)> {
+    /// Bound how long [`Self::connect_with_retry`] keeps retrying a
+    /// transient failure before giving up and returning it.
+    pub fn with_max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = max_elapsed;
+        self
+    }
+
+    fn parse_connection_string(conn_str: &str) -> Result<(ConnectTarget, String, String)> {
         let parts: Vec<&str> = conn_str.split("://").collect();
         if parts.len() != 2 || parts[0] != "poubelle" {
             return Err(Error::Parse(
@@ -48,184 +108,308 @@ impl PoubelleClient {
             return Err(Error::Parse("Invalid host:port format".to_string()));
         }
 
-        let port = host_port[1]
-            .parse::<u16>()
-            .map_err(|_| Error::Parse("Invalid port number".to_string()))?;
+        let host = percent_decode(host_port[0]);
+        let target = if host.starts_with('/') {
+            ConnectTarget::Unix(PathBuf::from(host))
+        } else {
+            let port = host_port[1]
+                .parse::<u16>()
+                .map_err(|_| Error::Parse("Invalid port number".to_string()))?;
+            ConnectTarget::Tcp { host, port }
+        };
 
-        Ok((
-            host_port[0].to_string(),
-            port,
-            auth[0].to_string(),
-            auth[1].to_string(),
-        ))
+        Ok((target, auth[0].to_string(), auth[1].to_string()))
     }
 
     pub async fn connect(&mut self) -> Result<()> {
-        let addr = format!("{}:{}", self.host, self.port);
-        let stream = TcpStream::connect(&addr)
-            .await
-            .map_err(|e| Error::Connection(e.to_string()))?;
+        let (mut reader, mut writer): (
+            Box<dyn AsyncRead + Unpin + Send>,
+            Box<dyn AsyncWrite + Unpin + Send>,
+        ) = match &self.target {
+            ConnectTarget::Tcp { host, port } => {
+                let addr = format!("{}:{}", host, port);
+                let stream = TcpStream::connect(&addr).await?;
+                let (r, w) = stream.into_split();
+                (Box::new(r), Box::new(w))
+            }
+            ConnectTarget::Unix(path) => {
+                let stream = UnixStream::connect(path).await?;
+                let (r, w) = stream.into_split();
+                (Box::new(r), Box::new(w))
+            }
+        };
 
-        let (mut reader, mut writer) = stream.into_split();
+        protocol::write_startup_message(&mut writer, &self.username, "poubelle").await?;
 
-        Self::wait_for_prompt(&mut reader, "Username: ").await?;
-        writer
-            .write_all(format!("{}\n", self.username).as_bytes())
-            .await?;
-        writer.flush().await?;
+        match protocol::read_backend_message(&mut reader).await? {
+            protocol::Backend::AuthRequest(3) => {}
+            protocol::Backend::ErrorResponse { message, .. } => {
+                return Err(Error::Connection(message));
+            }
+            _ => {
+                return Err(Error::Connection(
+                    "unexpected message during startup".to_string(),
+                ))
+            }
+        }
 
-        Self::wait_for_prompt(&mut reader, "Password: ").await?;
-        writer
-            .write_all(format!("{}\n", self.password).as_bytes())
-            .await?;
-        writer.flush().await?;
+        protocol::write_password_message(&mut writer, &self.password).await?;
 
-        Self::wait_for_prompt(&mut reader, "Connected to Poubelle DB").await?;
+        match protocol::read_backend_message(&mut reader).await? {
+            protocol::Backend::AuthRequest(0) => {}
+            protocol::Backend::ErrorResponse { .. } => return Err(Error::Authentication),
+            _ => {
+                return Err(Error::Connection(
+                    "unexpected message during authentication".to_string(),
+                ))
+            }
+        }
 
-        let stream = reader
-            .reunite(writer)
-            .map_err(|e| Error::Connection(e.to_string()))?;
-        self.stream = Some(stream);
+        match protocol::read_backend_message(&mut reader).await? {
+            protocol::Backend::ReadyForQuery(_) => {}
+            _ => {
+                return Err(Error::Connection(
+                    "expected ReadyForQuery after authentication".to_string(),
+                ))
+            }
+        }
 
+        self.reader = Some(reader);
+        self.writer = Some(writer);
         Ok(())
     }
 
-    pub async fn query(&mut self, sql: &str) -> Result<String> {
-        let stream = self
-            .stream
-            .take()
-            .ok_or_else(|| Error::Connection("Not connected".to_string()))?;
-
-        let (mut reader, mut writer) = stream.into_split();
+    /// Like [`Self::connect`], but retries transient failures (the server
+    /// refusing, resetting, or aborting the TCP connection — the shape of
+    /// things a server that's still starting up looks like) with
+    /// exponential backoff and jitter, up to [`Self::with_max_elapsed`]'s
+    /// bound. Anything else (a bad address, a failed handshake) is
+    /// permanent and returned immediately, same as `connect` would.
+    pub async fn connect_with_retry(&mut self, policy: RetryPolicy) -> Result<()> {
+        let start = Instant::now();
+        let mut backoff = policy.initial_backoff;
 
-        Self::wait_for_prompt(&mut reader, "poubelle> ").await?;
-
-        writer.write_all(format!("{}\n", sql).as_bytes()).await?;
-        writer.flush().await?;
+        loop {
+            match self.connect().await {
+                Ok(()) => return Ok(()),
+                Err(e) if is_transient(&e) && start.elapsed() < self.max_elapsed => {
+                    let jitter = Duration::from_millis(rand::thread_rng().gen_range(0..=50));
+                    tokio::time::sleep(backoff + jitter).await;
+                    backoff = (backoff * 2).min(policy.max_backoff);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
 
-        let result = Self::read_until_prompt(&mut reader, "poubelle> ").await?;
+    pub async fn query(&mut self, sql: &str) -> Result<String> {
+        let (rows, messages) = self.run_query(sql).await?;
 
-        let stream = reader
-            .reunite(writer)
-            .map_err(|e| Error::Connection(e.to_string()))?;
-        self.stream = Some(stream);
+        if !rows.is_empty() {
+            let mut output = String::new();
+            for row in &rows {
+                output.push_str(&format!("{:?}\n", row));
+            }
+            return Ok(output.trim_end().to_string());
+        }
 
-        Ok(result)
+        if messages.is_empty() {
+            return Ok("No rows".to_string());
+        }
+        Ok(messages.join("\n"))
     }
 
     pub async fn execute(&mut self, sql: &str) -> Result<Vec<Row>> {
-        let result = self.query(sql).await?;
-        Self::parse_rows(&result)
+        let (rows, _) = self.run_query(sql).await?;
+        Ok(rows)
     }
 
-    pub async fn close(&mut self) -> Result<()> {
-        if let Some(mut stream) = self.stream.take() {
-            stream.write_all(b"exit\n").await?;
-            stream.flush().await?;
-        }
-        Ok(())
+    /// Substitute `params` into `sql`'s positional placeholders (`$1`, `$2`,
+    /// ...) and run it.
+    ///
+    /// The wire protocol carries plain SQL text in the `Query` frame, so
+    /// there is no separate parse/bind step on the connection like a real
+    /// prepared-statement protocol would have; substitution happens here,
+    /// with values quoted and escaped the same way the server's own SQL
+    /// literals are written.
+    pub async fn query_prepared(&mut self, sql: &str, params: &[Value]) -> Result<String> {
+        let bound = Self::bind_params(sql, params)?;
+        self.query(&bound).await
     }
 
-    fn parse_rows(result: &str) -> Result<Vec<Row>> {
-        if result.is_empty() || result == "No rows" {
-            return Ok(Vec::new());
-        }
+    pub async fn execute_prepared(&mut self, sql: &str, params: &[Value]) -> Result<Vec<Row>> {
+        let bound = Self::bind_params(sql, params)?;
+        self.execute(&bound).await
+    }
 
-        if !result.contains('{') {
-            return Ok(Vec::new());
-        }
+    fn bind_params(sql: &str, params: &[Value]) -> Result<String> {
+        let mut result = String::with_capacity(sql.len());
+        let mut chars = sql.chars().peekable();
 
-        let mut rows = Vec::new();
-        for line in result.lines() {
-            if let Some(row) = Self::parse_row(line) {
-                rows.push(row);
+        while let Some(ch) = chars.next() {
+            if ch != '$' {
+                result.push(ch);
+                continue;
             }
-        }
 
-        Ok(rows)
-    }
+            let mut digits = String::new();
+            while let Some(d) = chars.peek() {
+                if d.is_ascii_digit() {
+                    digits.push(*d);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+
+            if digits.is_empty() {
+                result.push('$');
+                continue;
+            }
 
-    fn parse_row(line: &str) -> Option<Row> {
-        let line = line.trim();
-        if !line.starts_with('{') || !line.ends_with('}') {
-            return None;
+            let index: usize = digits
+                .parse()
+                .map_err(|_| Error::Parse(format!("Invalid parameter index: ${}", digits)))?;
+            let value = params.get(index - 1).ok_or_else(|| {
+                Error::Parse(format!("No value supplied for parameter ${}", index))
+            })?;
+            result.push_str(&Self::format_literal(value));
         }
 
-        let inner = &line[1..line.len() - 1];
-        let mut row = Row::new();
+        Ok(result)
+    }
 
-        let parts: Vec<&str> = inner.split(", ").collect();
-        for part in parts {
-            if let Some((key, value)) = part.split_once(": ") {
-                let clean_key = key.trim_matches('"');
-                let parsed_value = Self::parse_value(value);
-                row.insert(clean_key.to_string(), parsed_value);
-            }
+    fn format_literal(value: &Value) -> String {
+        match value {
+            Value::Int(n) => n.to_string(),
+            Value::Float(f) => f.to_string(),
+            Value::Bool(b) => b.to_string(),
+            Value::Text(s) => format!("'{}'", s.replace('\'', "''")),
+            Value::Timestamp(ts) => ts.to_string(),
+            Value::Null => "NULL".to_string(),
         }
+    }
 
-        if row.is_empty() {
-            None
-        } else {
-            Some(row)
+    pub async fn close(&mut self) -> Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            protocol::write_terminate(&mut writer).await.ok();
         }
+        self.reader = None;
+        Ok(())
     }
 
-    fn parse_value(value: &str) -> Value {
-        let value = value.trim();
+    /// Send `sql` as a `Query` message and collect every message of the
+    /// response — one `RowDescription`/`DataRow`*/`CommandComplete` group
+    /// per statement in a batch — until `ReadyForQuery`. The first
+    /// `ErrorResponse` seen is returned as an error; any statements after it
+    /// in the same batch go unread since the caller can't act on them
+    /// separately anyway.
+    ///
+    /// Every column comes back text-encoded (the server reports the
+    /// `text` OID for all of them), so rows are built with [`Value::Text`]
+    /// rather than the richer `Int`/`Float`/`Bool`/`Timestamp` variants —
+    /// the same thing a real postgres client sees without a type catalog
+    /// to decode against.
+    async fn run_query(&mut self, sql: &str) -> Result<(Vec<Row>, Vec<String>)> {
+        let writer = self
+            .writer
+            .as_mut()
+            .ok_or_else(|| Error::Connection("Not connected".to_string()))?;
+        protocol::write_query(writer, sql).await?;
 
-        if value == "Null" {
-            return Value::Null;
-        }
+        let reader = self
+            .reader
+            .as_mut()
+            .ok_or_else(|| Error::Connection("Not connected".to_string()))?;
 
-        if let Some(num_str) = value.strip_prefix("Int(").and_then(|s| s.strip_suffix(')')) {
-            if let Ok(num) = num_str.parse::<i64>() {
-                return Value::Int(num);
-            }
-        }
+        let mut rows = Vec::new();
+        let mut messages = Vec::new();
+        let mut columns: Vec<String> = Vec::new();
 
-        if let Some(text) = value
-            .strip_prefix("Text(")
-            .and_then(|s| s.strip_suffix(')'))
-        {
-            let text = text.trim_matches('"');
-            return Value::Text(text.to_string());
+        loop {
+            match protocol::read_backend_message(reader).await? {
+                protocol::Backend::RowDescription(cols) => columns = cols,
+                protocol::Backend::DataRow(values) => {
+                    let mut row = Row::new();
+                    for (name, value) in columns.iter().zip(values) {
+                        row.insert(name.clone(), value.map(Value::Text).unwrap_or(Value::Null));
+                    }
+                    rows.push(row);
+                }
+                protocol::Backend::CommandComplete(message) => messages.push(message),
+                protocol::Backend::ErrorResponse {
+                    code,
+                    message,
+                    redirect,
+                } => {
+                    Self::drain_until_ready(reader).await?;
+                    let message = match redirect {
+                        Some(node) => format!("{} (redirect to node {})", message, node),
+                        None => message,
+                    };
+                    return Err(Error::Query {
+                        message,
+                        sqlstate: SqlState::from_code(&code),
+                    });
+                }
+                protocol::Backend::ReadyForQuery(_) => break,
+                protocol::Backend::Notification { .. } => {
+                    // Unsolicited LISTEN delivery interleaved with a query
+                    // response; `query`/`execute` don't surface these.
+                }
+                protocol::Backend::AuthRequest(_) => {
+                    return Err(Error::Connection(
+                        "unexpected AuthRequest outside startup".to_string(),
+                    ));
+                }
+            }
         }
 
-        Value::Text(value.to_string())
+        Ok((rows, messages))
     }
 
-    async fn wait_for_prompt<R: AsyncReadExt + Unpin>(reader: &mut R, prompt: &str) -> Result<()> {
-        let mut buffer = Vec::new();
-        let mut byte = [0u8; 1];
-
+    async fn drain_until_ready<R: AsyncRead + Unpin>(reader: &mut R) -> Result<()> {
         loop {
-            reader.read_exact(&mut byte).await?;
-            buffer.push(byte[0]);
-
-            let s = String::from_utf8_lossy(&buffer);
-            if s.contains(prompt) {
-                break;
+            if let protocol::Backend::ReadyForQuery(_) =
+                protocol::read_backend_message(reader).await?
+            {
+                return Ok(());
             }
         }
-        Ok(())
     }
+}
 
-    async fn read_until_prompt<R: AsyncReadExt + Unpin>(
-        reader: &mut R,
-        prompt: &str,
-    ) -> Result<String> {
-        let mut buffer = Vec::new();
-        let mut byte = [0u8; 1];
-
-        loop {
-            reader.read_exact(&mut byte).await?;
-            buffer.push(byte[0]);
-
-            let s = String::from_utf8_lossy(&buffer);
-            if s.contains(prompt) {
-                let result = s.trim_end_matches(prompt).trim().to_string();
-                return Ok(result);
+/// Decode `%XX` escapes in a connection-string host component. Used to
+/// recover a Unix socket path written percent-encoded (e.g.
+/// `%2Fvar%2Frun%2Fpoubelle.sock`) in the host position.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Ok(byte) = u8::from_str_radix(&s[i + 1..i + 3], 16) {
+                out.push(byte);
+                i += 3;
+                continue;
             }
         }
+        out.push(bytes[i]);
+        i += 1;
     }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Whether `err` is worth retrying — the server not being up yet, rather
+/// than a permanent failure like a bad address or a rejected handshake.
+fn is_transient(err: &Error) -> bool {
+    matches!(
+        err,
+        Error::Io(e) if matches!(
+            e.kind(),
+            io::ErrorKind::ConnectionRefused
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+        )
+    )
 }
@@ -8,8 +8,8 @@ pub enum Error {
     #[error("Authentication failed")]
     Authentication,
 
-    #[error("Query error: {0}")]
-    Query(String),
+    #[error("Query error: {message}")]
+    Query { message: String, sqlstate: SqlState },
 
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
@@ -19,3 +19,49 @@ pub enum Error {
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+/// A SQLSTATE-style error class, mirroring `poubelle_engine::SqlState`
+/// independently of that crate (this SDK doesn't depend on the server or
+/// engine, the same way [`crate::types::Value`] duplicates `storage::Value`
+/// rather than sharing a crate) so callers can match on error category
+/// instead of parsing [`Error::Query`]'s message text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlState {
+    /// `22012` — division by zero.
+    DivisionByZero,
+    /// `42703` — reference to a column that doesn't exist.
+    UndefinedColumn,
+    /// `42804` — a value's type doesn't match what was expected.
+    DatatypeMismatch,
+    /// `42601` — malformed statement, including a column-count mismatch.
+    SyntaxErrorOrAccessRuleViolation,
+    Other(String),
+}
+
+static SQLSTATE_CODES: phf::Map<&'static str, SqlState> = phf::phf_map! {
+    "22012" => SqlState::DivisionByZero,
+    "42703" => SqlState::UndefinedColumn,
+    "42804" => SqlState::DatatypeMismatch,
+    "42601" => SqlState::SyntaxErrorOrAccessRuleViolation,
+};
+
+impl SqlState {
+    pub fn code(&self) -> &str {
+        match self {
+            SqlState::DivisionByZero => "22012",
+            SqlState::UndefinedColumn => "42703",
+            SqlState::DatatypeMismatch => "42804",
+            SqlState::SyntaxErrorOrAccessRuleViolation => "42601",
+            SqlState::Other(code) => code,
+        }
+    }
+
+    /// Look up the class for a code read off the wire, falling back to
+    /// `Other` for any code this enum doesn't name a variant for.
+    pub fn from_code(code: &str) -> SqlState {
+        SQLSTATE_CODES
+            .get(code)
+            .cloned()
+            .unwrap_or_else(|| SqlState::Other(code.to_string()))
+    }
+}
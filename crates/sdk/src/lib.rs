@@ -1,7 +1,8 @@
 mod client;
 mod error;
+mod protocol;
 mod types;
 
-pub use client::PoubelleClient;
-pub use error::{Error, Result};
+pub use client::{PoubelleClient, RetryPolicy};
+pub use error::{Error, Result, SqlState};
 pub use types::{Row, Value};
@@ -0,0 +1,224 @@
+//! The real PostgreSQL frontend/backend wire protocol (v3) that the server
+//! speaks: see `poubelle_server::pg_protocol` for the authoritative byte
+//! layout this mirrors (this SDK doesn't depend on the server crate, so it
+//! keeps its own copy, same as [`crate::types::Value`] duplicates
+//! `storage::Value`).
+//!
+//! Every tagged message is a one-byte tag, a 4-byte big-endian length
+//! (covering the length field itself but not the tag byte), and a
+//! tag-specific payload.
+
+use crate::error::{Error, Result};
+use std::collections::HashMap;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Protocol version 3.0 (major 3, minor 0), sent as the untagged
+/// StartupMessage's second Int32.
+const PROTOCOL_VERSION_3: i32 = 196608;
+
+/// One backend message in the simple query cycle.
+pub enum Backend {
+    /// The authentication type code requested: `3` for
+    /// AuthenticationCleartextPassword, `0` for AuthenticationOk.
+    AuthRequest(i32),
+    /// Transaction status byte (`I`/`T`/`E`).
+    ReadyForQuery(u8),
+    RowDescription(Vec<String>),
+    /// `None` entries are SQL `NULL`.
+    DataRow(Vec<Option<String>>),
+    CommandComplete(String),
+    ErrorResponse {
+        code: String,
+        message: String,
+        /// Poubelle-specific: set when the error's `Detail` field named a
+        /// node to redirect to (see `poubelle_server::pg_protocol`).
+        redirect: Option<u64>,
+    },
+    Notification {
+        channel: String,
+        payload: String,
+    },
+}
+
+/// Write the untagged StartupMessage.
+pub async fn write_startup_message<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    user: &str,
+    database: &str,
+) -> Result<()> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&PROTOCOL_VERSION_3.to_be_bytes());
+    body.extend_from_slice(b"user");
+    body.push(0);
+    body.extend_from_slice(user.as_bytes());
+    body.push(0);
+    body.extend_from_slice(b"database");
+    body.push(0);
+    body.extend_from_slice(database.as_bytes());
+    body.push(0);
+    body.push(0); // parameter list terminator
+
+    let len = (body.len() + 4) as u32;
+    writer.write_u32(len).await?;
+    writer.write_all(&body).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+async fn write_message<W, F>(writer: &mut W, tag: u8, body: F) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+    F: FnOnce(&mut Vec<u8>),
+{
+    let mut payload = Vec::new();
+    body(&mut payload);
+    let len = (payload.len() + 4) as u32;
+    writer.write_u8(tag).await?;
+    writer.write_u32(len).await?;
+    writer.write_all(&payload).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+pub async fn write_password_message<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    password: &str,
+) -> Result<()> {
+    write_message(writer, b'p', |buf| {
+        buf.extend_from_slice(password.as_bytes());
+        buf.push(0);
+    })
+    .await
+}
+
+pub async fn write_query<W: AsyncWrite + Unpin>(writer: &mut W, sql: &str) -> Result<()> {
+    write_message(writer, b'Q', |buf| {
+        buf.extend_from_slice(sql.as_bytes());
+        buf.push(0);
+    })
+    .await
+}
+
+/// Send `Terminate`, the clean-close frontend message.
+pub async fn write_terminate<W: AsyncWrite + Unpin>(writer: &mut W) -> Result<()> {
+    write_message(writer, b'X', |_| {}).await
+}
+
+async fn read_message<R: AsyncRead + Unpin>(reader: &mut R) -> Result<(u8, Vec<u8>)> {
+    let tag = reader.read_u8().await?;
+    let len = reader.read_u32().await?;
+    let body_len = (len as usize).saturating_sub(4);
+    let mut payload = vec![0u8; body_len];
+    reader.read_exact(&mut payload).await?;
+    Ok((tag, payload))
+}
+
+fn cstr(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+fn parse_error_fields(payload: &[u8]) -> HashMap<u8, String> {
+    let mut fields = HashMap::new();
+    let mut pos = 0;
+    while pos < payload.len() && payload[pos] != 0 {
+        let field_type = payload[pos];
+        pos += 1;
+        let end = payload[pos..]
+            .iter()
+            .position(|&b| b == 0)
+            .map(|i| pos + i)
+            .unwrap_or(payload.len());
+        fields.insert(
+            field_type,
+            String::from_utf8_lossy(&payload[pos..end]).into_owned(),
+        );
+        pos = end + 1;
+    }
+    fields
+}
+
+/// Read the next backend message off the wire.
+pub async fn read_backend_message<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Backend> {
+    let (tag, payload) = read_message(reader).await?;
+    match tag {
+        b'R' => {
+            let code = i32::from_be_bytes(payload[0..4].try_into().unwrap());
+            Ok(Backend::AuthRequest(code))
+        }
+        b'Z' => Ok(Backend::ReadyForQuery(payload[0])),
+        b'T' => {
+            let count = i16::from_be_bytes(payload[0..2].try_into().unwrap()) as usize;
+            let mut columns = Vec::with_capacity(count);
+            let mut pos = 2;
+            for _ in 0..count {
+                let end = payload[pos..]
+                    .iter()
+                    .position(|&b| b == 0)
+                    .map(|i| pos + i)
+                    .unwrap_or(payload.len());
+                columns.push(String::from_utf8_lossy(&payload[pos..end]).into_owned());
+                // name cstring + table OID (4) + column attr (2) + type OID
+                // (4) + type length (2) + type modifier (4) + format code (2)
+                pos = end + 1 + 4 + 2 + 4 + 2 + 4 + 2;
+            }
+            Ok(Backend::RowDescription(columns))
+        }
+        b'D' => {
+            let count = i16::from_be_bytes(payload[0..2].try_into().unwrap()) as usize;
+            let mut values = Vec::with_capacity(count);
+            let mut pos = 2;
+            for _ in 0..count {
+                let len = i32::from_be_bytes(payload[pos..pos + 4].try_into().unwrap());
+                pos += 4;
+                if len < 0 {
+                    values.push(None);
+                } else {
+                    let len = len as usize;
+                    values.push(Some(
+                        String::from_utf8_lossy(&payload[pos..pos + len]).into_owned(),
+                    ));
+                    pos += len;
+                }
+            }
+            Ok(Backend::DataRow(values))
+        }
+        b'C' => Ok(Backend::CommandComplete(cstr(&payload))),
+        b'E' => {
+            let fields = parse_error_fields(&payload);
+            let redirect = fields
+                .get(&b'D')
+                .and_then(|d| d.strip_prefix("redirect to node "))
+                .and_then(|n| n.parse().ok());
+            Ok(Backend::ErrorResponse {
+                code: fields.get(&b'C').cloned().unwrap_or_default(),
+                message: fields.get(&b'M').cloned().unwrap_or_default(),
+                redirect,
+            })
+        }
+        b'A' => {
+            let channel_end = payload[4..]
+                .iter()
+                .position(|&b| b == 0)
+                .map(|i| 4 + i)
+                .unwrap_or(payload.len());
+            let channel = String::from_utf8_lossy(&payload[4..channel_end]).into_owned();
+            let payload_start = channel_end + 1;
+            let payload_end = payload[payload_start..]
+                .iter()
+                .position(|&b| b == 0)
+                .map(|i| payload_start + i)
+                .unwrap_or(payload.len());
+            let notif_payload =
+                String::from_utf8_lossy(&payload[payload_start..payload_end]).into_owned();
+            Ok(Backend::Notification {
+                channel,
+                payload: notif_payload,
+            })
+        }
+        other => Err(Error::Connection(format!(
+            "unknown backend message {:#04x}",
+            other
+        ))),
+    }
+}
@@ -7,6 +7,10 @@ pub enum Value {
     Int(i64),
     Text(String),
     Null,
+    Float(f64),
+    Bool(bool),
+    /// Epoch milliseconds.
+    Timestamp(i64),
 }
 
 pub type Row = HashMap<String, Value>;
@@ -1,39 +1,62 @@
-use sha2::{Digest, Sha256};
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use engine::Engine;
 use std::collections::HashMap;
 
+/// `username -> PHC-format Argon2id hash` (`$argon2id$v=19$...`), persisted
+/// through `engine`'s storage as a reserved system blob rather than
+/// cleartext, so `POUBELLE_PASSWORD` and any future user table don't leak
+/// verbatim.
 pub struct AuthStore {
+    engine: Engine,
     users: HashMap<String, String>,
 }
 
 impl AuthStore {
-    pub fn new() -> Self {
-        Self {
-            users: HashMap::new(),
+    /// Load the persisted user table, migrating any entry that doesn't
+    /// parse as a PHC-format hash — a plaintext value seen for the first
+    /// time — to an Argon2id hash before it's used.
+    pub async fn load(engine: Engine) -> Self {
+        let mut users = engine.load_auth_users().await.unwrap_or_default();
+
+        let mut migrated = false;
+        for hash in users.values_mut() {
+            if PasswordHash::new(hash).is_err() {
+                *hash = Self::hash_password(hash);
+                migrated = true;
+            }
+        }
+        if migrated {
+            engine.save_auth_users(&users).await.ok();
         }
+
+        Self { engine, users }
     }
 
-    pub fn add_user(&mut self, username: String, password: String) {
+    pub async fn add_user(&mut self, username: String, password: String) {
         let hash = Self::hash_password(&password);
         self.users.insert(username, hash);
+        self.engine.save_auth_users(&self.users).await.ok();
     }
 
     pub fn verify(&self, username: &str, password: &str) -> bool {
-        if let Some(stored_hash) = self.users.get(username) {
-            let hash = Self::hash_password(password);
-            return &hash == stored_hash;
-        }
-        false
+        let Some(stored_hash) = self.users.get(username) else {
+            return false;
+        };
+        let Ok(parsed) = PasswordHash::new(stored_hash) else {
+            return false;
+        };
+        Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok()
     }
 
     fn hash_password(password: &str) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(password.as_bytes());
-        format!("{:x}", hasher.finalize())
-    }
-}
-
-impl Default for AuthStore {
-    fn default() -> Self {
-        Self::new()
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .expect("argon2 hashing failed")
+            .to_string()
     }
 }
@@ -0,0 +1,95 @@
+//! [`engine::ClusterTransport`] implementation that forwards statements to
+//! peer nodes as an ordinary client, reusing `poubelle_sdk::PoubelleClient`
+//! rather than a second copy of the frontend wire protocol.
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use engine::{ClusterError, ClusterNodeId, ClusterTransport, NodeAddr};
+use poubelle_sdk::PoubelleClient;
+use std::sync::Arc;
+use storage::{Row, Value};
+use tokio::sync::Mutex;
+
+/// One connection per peer, authenticated once and reused across forwarded
+/// statements — mirrors how a real client connects once rather than per
+/// query.
+pub struct TcpClusterTransport {
+    username: String,
+    password: String,
+    peers: DashMap<ClusterNodeId, Arc<Mutex<PoubelleClient>>>,
+}
+
+impl TcpClusterTransport {
+    pub fn new(username: String, password: String) -> Self {
+        Self {
+            username,
+            password,
+            peers: DashMap::new(),
+        }
+    }
+
+    async fn client_for(
+        &self,
+        node: &ClusterNodeId,
+        addr: &NodeAddr,
+    ) -> Result<Arc<Mutex<PoubelleClient>>, ClusterError> {
+        if let Some(client) = self.peers.get(node) {
+            return Ok(client.clone());
+        }
+
+        let connection_string = format!(
+            "poubelle://{}:{}@{}:{}",
+            self.username, self.password, addr.host, addr.port
+        );
+        let mut client = PoubelleClient::new(&connection_string)
+            .map_err(|e| ClusterError::Transport(node.clone(), e.to_string()))?;
+        client
+            .connect()
+            .await
+            .map_err(|e| ClusterError::Transport(node.clone(), e.to_string()))?;
+
+        let client = Arc::new(Mutex::new(client));
+        self.peers.insert(node.clone(), client.clone());
+        Ok(client)
+    }
+}
+
+#[async_trait]
+impl ClusterTransport for TcpClusterTransport {
+    async fn forward(
+        &self,
+        node: &ClusterNodeId,
+        addr: &NodeAddr,
+        sql: &str,
+    ) -> Result<Vec<Row>, ClusterError> {
+        let client = self.client_for(node, addr).await?;
+        let rows = client
+            .lock()
+            .await
+            .execute(sql)
+            .await
+            .map_err(|e| ClusterError::Transport(node.clone(), e.to_string()))?;
+
+        Ok(rows.into_iter().map(sdk_row_to_storage_row).collect())
+    }
+}
+
+fn sdk_row_to_storage_row(row: poubelle_sdk::Row) -> Row {
+    Row {
+        data: row
+            .into_iter()
+            .map(|(column, value)| (column, sdk_value_to_storage_value(value)))
+            .collect(),
+    }
+}
+
+fn sdk_value_to_storage_value(value: poubelle_sdk::Value) -> Value {
+    match value {
+        poubelle_sdk::Value::Int(n) => Value::Int(n),
+        poubelle_sdk::Value::Text(s) => Value::Text(s),
+        poubelle_sdk::Value::Null => Value::Null,
+        poubelle_sdk::Value::Float(f) => Value::Float(f),
+        poubelle_sdk::Value::Bool(b) => Value::Bool(b),
+        poubelle_sdk::Value::Timestamp(ts) => Value::Timestamp(ts),
+    }
+}
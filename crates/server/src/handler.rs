@@ -1,61 +1,196 @@
-use db_engine::{Engine, QueryResult};
-use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
-use tokio::sync::Mutex;
+use crate::pg_protocol::{self, Frontend};
+use engine::{Engine, EngineError, QueryResult};
+use std::time::Instant;
+use storage::Row;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::broadcast;
+use tracing::Instrument;
 
+/// `reader`/`writer` are boxed rather than a concrete `TcpStream` half
+/// pair so the same handshake/query loop runs unchanged whether the
+/// connection came in plaintext or through [`crate::tls`]'s
+/// `TlsAcceptor` — mirrors the SDK's own `ConnectTarget` split in
+/// `sdk::client::PoubelleClient`. `engine` is a cheaply-cloneable handle
+/// (see `engine::Engine`), not a lock guard, so one slow connection's
+/// query never blocks another's.
 pub async fn handle_client(
-    mut reader: BufReader<OwnedReadHalf>,
-    mut writer: OwnedWriteHalf,
-    engine: Arc<Mutex<Engine>>,
+    mut reader: Box<dyn AsyncRead + Unpin + Send>,
+    mut writer: Box<dyn AsyncWrite + Unpin + Send>,
+    engine: Engine,
 ) {
+    let mut subscription: Option<(String, broadcast::Receiver<Row>)> = None;
+    // Postgres's transaction status byte (`I` idle, `T` in a transaction,
+    // `E` in a failed transaction needing ROLLBACK), echoed back on every
+    // ReadyForQuery so clients like psql can show it in their prompt.
+    let mut tx_status = b'I';
+
     loop {
-        writer.write_all(b"poubelle> ").await.ok();
-        writer.flush().await.ok();
+        let message = if let Some((_, rx)) = subscription.as_mut() {
+            tokio::select! {
+                message = pg_protocol::read_frontend_message(&mut reader) => message,
+                event = rx.recv() => {
+                    match event {
+                        Ok(row) => {
+                            let channel = subscription.as_ref().unwrap().0.clone();
+                            let payload = format!("{:?}", row.data);
+                            pg_protocol::write_notification(&mut writer, &channel, &payload).await.ok();
+                        }
+                        Err(broadcast::error::RecvError::Lagged(_)) => {
+                            // Some events were dropped; the subscriber just
+                            // misses them rather than getting kicked off.
+                        }
+                        Err(broadcast::error::RecvError::Closed) => {
+                            subscription = None;
+                        }
+                    }
+                    continue;
+                }
+            }
+        } else {
+            pg_protocol::read_frontend_message(&mut reader).await
+        };
 
-        let mut line = String::new();
-        match reader.read_line(&mut line).await {
-            Ok(0) | Err(_) => break,
-            Ok(_) => {}
-        }
+        let query = match message {
+            Ok(Frontend::Query(query)) => query,
+            Ok(Frontend::Terminate) => break,
+            Err(_) => break,
+        };
+        let query = query.trim();
 
-        let query = line.trim();
         if query.is_empty() {
+            pg_protocol::write_ready_for_query(&mut writer, tx_status)
+                .await
+                .ok();
             continue;
         }
 
         if query.eq_ignore_ascii_case("exit") || query.eq_ignore_ascii_case("quit") {
-            writer.write_all(b"Goodbye\n").await.ok();
             break;
         }
 
-        let mut engine = engine.lock().await;
-        match engine.execute_query(query) {
-            Ok(result) => {
-                let output = format_result(result);
-                writer.write_all(output.as_bytes()).await.ok();
-            }
-            Err(e) => {
-                let msg = format!("Error: {}\n", e);
-                writer.write_all(msg.as_bytes()).await.ok();
-            }
+        if let Some(table) = query
+            .strip_prefix("LISTEN ")
+            .or_else(|| query.strip_prefix("listen "))
+        {
+            let table = table.trim().to_string();
+            let rx = engine.listen(&table);
+            let message = format!("LISTEN {}", table);
+            subscription = Some((table, rx));
+            pg_protocol::write_command_complete(&mut writer, &message)
+                .await
+                .ok();
+            pg_protocol::write_ready_for_query(&mut writer, tx_status)
+                .await
+                .ok();
+            continue;
         }
-    }
-}
 
-fn format_result(result: QueryResult) -> String {
-    match result {
-        QueryResult::Success(msg) => format!("{}\n", msg),
-        QueryResult::Rows(rows) => {
-            if rows.is_empty() {
-                return "No rows\n".to_string();
-            }
+        if query.eq_ignore_ascii_case("unlisten") {
+            subscription = None;
+            pg_protocol::write_command_complete(&mut writer, "UNLISTEN")
+                .await
+                .ok();
+            pg_protocol::write_ready_for_query(&mut writer, tx_status)
+                .await
+                .ok();
+            continue;
+        }
+
+        let verb = query
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_uppercase();
+        let statement_span = tracing::info_span!(
+            "statement",
+            verb = %verb,
+            row_count = tracing::field::Empty,
+            elapsed_ms = tracing::field::Empty
+        );
+        let started_at = Instant::now();
+        let results = engine
+            .execute_batch(query)
+            .instrument(statement_span.clone())
+            .await;
+        let row_count: usize = results
+            .iter()
+            .map(|result| match result {
+                Ok(QueryResult::Rows(rows)) => rows.len(),
+                _ => 0,
+            })
+            .sum();
+        statement_span.record("row_count", row_count);
+        statement_span.record("elapsed_ms", started_at.elapsed().as_millis() as u64);
 
-            let mut output = String::new();
-            for row in rows {
-                output.push_str(&format!("{:?}\n", row.data));
+        for result in results {
+            match result {
+                Ok(QueryResult::Success(message)) => {
+                    match message.as_str() {
+                        "BEGIN" => tx_status = b'T',
+                        "COMMIT" | "ROLLBACK" => tx_status = b'I',
+                        _ => {}
+                    }
+                    pg_protocol::write_command_complete(&mut writer, &message)
+                        .await
+                        .ok();
+                }
+                Ok(QueryResult::Rows(rows)) => {
+                    let columns: Vec<String> = rows
+                        .first()
+                        .map(|row| row.data.keys().cloned().collect())
+                        .unwrap_or_default();
+                    pg_protocol::write_row_description(&mut writer, &columns)
+                        .await
+                        .ok();
+
+                    let count = rows.len();
+                    for row in &rows {
+                        let values: Vec<Option<String>> = columns
+                            .iter()
+                            .map(|col| row.data.get(col).and_then(pg_protocol::value_to_text))
+                            .collect();
+                        pg_protocol::write_data_row(&mut writer, &values).await.ok();
+                    }
+
+                    pg_protocol::write_command_complete(&mut writer, &format!("SELECT {}", count))
+                        .await
+                        .ok();
+                }
+                Err(EngineError::NotLeader(leader)) => {
+                    pg_protocol::write_error_response(
+                        &mut writer,
+                        // Mirrors EngineError::NotLeader's own sqlstate().
+                        "58000",
+                        match leader {
+                            Some(_) => "not the leader",
+                            None => "not the leader, and no leader is known yet",
+                        },
+                        leader,
+                    )
+                    .await
+                    .ok();
+                    if tx_status == b'T' {
+                        tx_status = b'E';
+                    }
+                }
+                Err(e) => {
+                    pg_protocol::write_error_response(
+                        &mut writer,
+                        e.sqlstate().code(),
+                        &e.to_string(),
+                        None,
+                    )
+                    .await
+                    .ok();
+                    if tx_status == b'T' {
+                        tx_status = b'E';
+                    }
+                }
             }
-            output
         }
+
+        pg_protocol::write_ready_for_query(&mut writer, tx_status)
+            .await
+            .ok();
     }
 }
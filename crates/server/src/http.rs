@@ -1,21 +1,41 @@
+use crate::auth::AuthStore;
 use axum::{
-    extract::State,
-    http::StatusCode,
+    body::Body,
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
     response::{IntoResponse, Response},
     routing::post,
     Json, Router,
 };
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use engine::{Engine, QueryResult};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
 use std::sync::Arc;
+use storage::Row;
 use tokio::sync::Mutex;
 use tower_http::cors::CorsLayer;
 
+/// Default number of rows `/query` returns when the request doesn't specify
+/// a `limit`.
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+#[derive(Clone)]
+struct AppState {
+    engine: Engine,
+    auth: Arc<Mutex<AuthStore>>,
+}
+
 #[derive(Deserialize)]
 struct QueryRequest {
     query: String,
+    /// Zero-based index of the first row to return.
+    #[serde(default)]
+    offset: usize,
+    /// Maximum number of rows to return. Defaults to `DEFAULT_PAGE_SIZE`.
+    limit: Option<usize>,
 }
 
 #[derive(Serialize)]
@@ -23,6 +43,11 @@ struct QueryRequest {
 enum QueryResponse {
     Rows {
         rows: Vec<HashMap<String, JsonValue>>,
+        /// Total rows the query matched, before pagination was applied.
+        total: usize,
+        offset: usize,
+        limit: usize,
+        has_more: bool,
     },
     Success {
         message: String,
@@ -36,55 +61,144 @@ struct ErrorResponse {
 
 enum ApiError {
     Engine(String),
+    Unauthorized,
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         let (status, message) = match self {
             ApiError::Engine(msg) => (StatusCode::BAD_REQUEST, msg),
+            ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized".to_string()),
         };
 
         (status, Json(ErrorResponse { error: message })).into_response()
     }
 }
 
+/// Requires HTTP Basic auth on every request that passes through it,
+/// verifying the credentials against the shared [`AuthStore`].
+async fn auth_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let credentials = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Basic "))
+        .and_then(|encoded| BASE64.decode(encoded).ok())
+        .and_then(|decoded| String::from_utf8(decoded).ok())
+        .ok_or(ApiError::Unauthorized)?;
+
+    let (username, password) = credentials.split_once(':').ok_or(ApiError::Unauthorized)?;
+
+    if !state.auth.lock().await.verify(username, password) {
+        return Err(ApiError::Unauthorized);
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Convert a storage row into its JSON representation, as returned by both
+/// the paginated and streaming query endpoints.
+fn row_to_json(row: Row) -> HashMap<String, JsonValue> {
+    row.data
+        .into_iter()
+        .map(|(k, v)| {
+            let json_value = match v {
+                storage::Value::Int(i) => JsonValue::Number(i.into()),
+                storage::Value::Text(s) => JsonValue::String(s),
+                storage::Value::Null => JsonValue::Null,
+                storage::Value::Float(f) => serde_json::Number::from_f64(f)
+                    .map(JsonValue::Number)
+                    .unwrap_or(JsonValue::Null),
+                storage::Value::Bool(b) => JsonValue::Bool(b),
+                storage::Value::Timestamp(ts) => {
+                    chrono::DateTime::<chrono::Utc>::from_timestamp_millis(ts)
+                        .map(|dt| JsonValue::String(dt.to_rfc3339()))
+                        .unwrap_or(JsonValue::Null)
+                }
+            };
+            (k, json_value)
+        })
+        .collect()
+}
+
 async fn query_handler(
-    State(engine): State<Arc<Mutex<Engine>>>,
+    State(state): State<AppState>,
     Json(payload): Json<QueryRequest>,
 ) -> Result<Json<QueryResponse>, ApiError> {
-    let mut engine = engine.lock().await;
-
-    let result = engine
+    let result = state
+        .engine
         .execute_query(&payload.query)
+        .await
         .map_err(|e| ApiError::Engine(format!("{}", e)))?;
 
     let response = match result {
         QueryResult::Success(msg) => QueryResponse::Success { message: msg },
         QueryResult::Rows(rows) => {
+            let total = rows.len();
+            let offset = payload.offset;
+            let limit = payload.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+
             let parsed_rows: Vec<HashMap<String, JsonValue>> = rows
                 .into_iter()
-                .map(|row| {
-                    row.data
-                        .into_iter()
-                        .map(|(k, v)| {
-                            let json_value = match v {
-                                storage::Value::Int(i) => JsonValue::Number(i.into()),
-                                storage::Value::Text(s) => JsonValue::String(s),
-                                storage::Value::Null => JsonValue::Null,
-                            };
-                            (k, json_value)
-                        })
-                        .collect()
-                })
+                .skip(offset)
+                .take(limit)
+                .map(row_to_json)
                 .collect();
 
-            QueryResponse::Rows { rows: parsed_rows }
+            let has_more = offset + parsed_rows.len() < total;
+
+            QueryResponse::Rows {
+                rows: parsed_rows,
+                total,
+                offset,
+                limit,
+                has_more,
+            }
         }
     };
 
     Ok(Json(response))
 }
 
+/// Like `/query`, but streams matching rows back as newline-delimited JSON
+/// (one row object per line) instead of buffering the whole response body,
+/// and isn't subject to `/query`'s pagination.
+async fn query_stream_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<QueryRequest>,
+) -> Result<Response, ApiError> {
+    let result = state
+        .engine
+        .execute_query(&payload.query)
+        .await
+        .map_err(|e| ApiError::Engine(format!("{}", e)))?;
+
+    let rows = match result {
+        QueryResult::Success(msg) => {
+            return Ok(Json(QueryResponse::Success { message: msg }).into_response());
+        }
+        QueryResult::Rows(rows) => rows,
+    };
+
+    let lines = rows.into_iter().map(|row| {
+        let mut line = serde_json::to_string(&row_to_json(row)).unwrap_or_default();
+        line.push('\n');
+        Ok::<_, std::io::Error>(line)
+    });
+
+    let body = Body::from_stream(tokio_stream::iter(lines));
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/x-ndjson")
+        .body(body)
+        .unwrap())
+}
+
 async fn health_handler() -> impl IntoResponse {
     Json(serde_json::json!({
         "status": "healthy",
@@ -92,25 +206,51 @@ async fn health_handler() -> impl IntoResponse {
     }))
 }
 
-pub fn create_router(engine: Arc<Mutex<Engine>>) -> Router {
-    Router::new()
+pub fn create_router(engine: Engine, auth: Arc<Mutex<AuthStore>>) -> Router {
+    let state = AppState { engine, auth };
+
+    // The query endpoint requires Basic auth; health checks don't.
+    let protected = Router::new()
         .route("/query", post(query_handler))
+        .route("/query/stream", post(query_stream_handler))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth_middleware,
+        ));
+
+    Router::new()
+        .merge(protected)
         .route("/health", axum::routing::get(health_handler))
         .layer(CorsLayer::permissive())
-        .with_state(engine)
+        .with_state(state)
 }
 
 pub async fn start_http_server(
-    engine: Arc<Mutex<Engine>>,
+    engine: Engine,
+    auth: Arc<Mutex<AuthStore>>,
     host: String,
     port: String,
 ) -> anyhow::Result<()> {
-    let app = create_router(engine);
+    let app = create_router(engine, auth);
     let bind_addr = format!("{}:{}", host, port);
-    let listener = tokio::net::TcpListener::bind(&bind_addr).await?;
 
-    println!("HTTP API listening on {}", bind_addr);
-    axum::serve(listener, app).await?;
+    // Same `POUBELLE_TLS_CERT`/`POUBELLE_TLS_KEY` env vars the TCP listener
+    // uses — see `crate::tls` — so the HTTP API serves HTTPS whenever the
+    // postgres wire protocol listener does.
+    match crate::tls::rustls_config_from_env().await? {
+        Some(tls_config) => {
+            let addr = bind_addr.parse()?;
+            println!("HTTP API listening on {} (TLS)", bind_addr);
+            axum_server::bind_rustls(addr, tls_config)
+                .serve(app.into_make_service())
+                .await?;
+        }
+        None => {
+            let listener = tokio::net::TcpListener::bind(&bind_addr).await?;
+            println!("HTTP API listening on {}", bind_addr);
+            axum::serve(listener, app).await?;
+        }
+    }
 
     Ok(())
 }
@@ -1,21 +1,28 @@
 mod auth;
+mod cluster_transport;
 mod handler;
 mod http;
+mod pg_protocol;
+mod telemetry;
+mod tls;
 
 use auth::AuthStore;
-use engine::Engine;
+use cluster_transport::TcpClusterTransport;
+use engine::{ClusterMap, Engine};
 use handler::handle_client;
 use std::env;
 use std::path::PathBuf;
 use std::sync::Arc;
 use storage::Storage;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpListener;
 use tokio::sync::Mutex;
+use tracing::Instrument;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenvy::dotenv().ok();
+    telemetry::init()?;
 
     let data_dir = env::var("POUBELLE_DATA_DIR").unwrap_or_else(|_| "./data".to_string());
     let tcp_host = env::var("POUBELLE_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
@@ -25,69 +32,137 @@ async fn main() -> anyhow::Result<()> {
     let username = env::var("POUBELLE_USERNAME").unwrap_or_else(|_| "admin".to_string());
     let password = env::var("POUBELLE_PASSWORD").unwrap_or_else(|_| "admin".to_string());
 
-    let storage = Storage::open(PathBuf::from(&data_dir))?;
-    let engine = Arc::new(Mutex::new(Engine::new(storage)));
+    let storage = Storage::open(PathBuf::from(&data_dir)).await?;
+    let engine = Engine::new(storage);
 
-    let auth_store = Arc::new(Mutex::new(AuthStore::new()));
-    auth_store.lock().await.add_user(username, password);
+    let engine = match ClusterMap::from_env()? {
+        Some(map) => {
+            let transport =
+                TcpClusterTransport::new(map.username().to_string(), map.password().to_string());
+            engine.with_cluster(map, Arc::new(transport))
+        }
+        None => engine,
+    };
+
+    let auth_store = Arc::new(Mutex::new(AuthStore::load(engine.clone()).await));
+    auth_store.lock().await.add_user(username, password).await;
 
-    let engine_for_http = Arc::clone(&engine);
+    let engine_for_http = engine.clone();
     let http_host_clone = http_host.clone();
     let http_port_clone = http_port.clone();
 
+    let auth_for_http = Arc::clone(&auth_store);
+
     tokio::spawn(async move {
-        if let Err(e) =
-            http::start_http_server(engine_for_http, http_host_clone, http_port_clone).await
+        if let Err(e) = http::start_http_server(
+            engine_for_http,
+            auth_for_http,
+            http_host_clone,
+            http_port_clone,
+        )
+        .await
         {
             eprintln!("HTTP server error: {}", e);
         }
     });
 
+    let tls_acceptor = tls::acceptor_from_env()?;
+
     let tcp_bind_addr = format!("{}:{}", tcp_host, tcp_port);
     let listener = TcpListener::bind(&tcp_bind_addr).await?;
     println!("Poubelle DB started");
-    println!("  TCP  server: {}", tcp_bind_addr);
+    println!(
+        "  TCP  server: {} ({})",
+        tcp_bind_addr,
+        if tls_acceptor.is_some() {
+            "TLS"
+        } else {
+            "plaintext"
+        }
+    );
     println!("  HTTP server: {}:{}", http_host, http_port);
 
     loop {
         let (socket, addr) = listener.accept().await?;
         println!("TCP connection from: {}", addr);
 
-        let engine = Arc::clone(&engine);
+        let engine = engine.clone();
         let auth = Arc::clone(&auth_store);
-
-        tokio::spawn(async move {
-            let (reader, mut writer) = socket.into_split();
-            let mut reader = BufReader::new(reader);
-
-            writer.write_all(b"Username: ").await.ok();
-            writer.flush().await.ok();
-
-            let mut username = String::new();
-            if reader.read_line(&mut username).await.is_err() {
-                return;
+        let tls_acceptor = tls_acceptor.clone();
+        let connection_span = tracing::info_span!("connection", peer = %addr);
+
+        tokio::spawn(
+            async move {
+                let (mut reader, mut writer): (
+                    Box<dyn AsyncRead + Unpin + Send>,
+                    Box<dyn AsyncWrite + Unpin + Send>,
+                ) = match tls_acceptor {
+                    Some(acceptor) => {
+                        let stream = match acceptor.accept(socket).await {
+                            Ok(stream) => stream,
+                            Err(_) => return,
+                        };
+                        let (reader, writer) = tokio::io::split(stream);
+                        (Box::new(reader), Box::new(writer))
+                    }
+                    None => {
+                        let (reader, writer) = socket.into_split();
+                        (Box::new(reader), Box::new(writer))
+                    }
+                };
+
+                let startup =
+                    match pg_protocol::read_startup_message(&mut reader, &mut writer).await {
+                        Ok(startup) => startup,
+                        Err(_) => return,
+                    };
+
+                let auth_span = tracing::info_span!(
+                    "auth",
+                    user = %startup.user,
+                    success = tracing::field::Empty
+                );
+                let authenticated = async {
+                    pg_protocol::write_auth_cleartext(&mut writer).await.ok();
+
+                    let password = match pg_protocol::read_password_message(&mut reader).await {
+                        Ok(password) => password,
+                        Err(_) => return None,
+                    };
+
+                    // Never log `password` itself — only the pass/fail outcome.
+                    Some(auth.lock().await.verify(&startup.user, &password))
+                }
+                .instrument(auth_span.clone())
+                .await;
+
+                let authenticated = match authenticated {
+                    Some(authenticated) => authenticated,
+                    None => return,
+                };
+                auth_span.record("success", authenticated);
+
+                if !authenticated {
+                    // Postgres's own code for a bad password.
+                    pg_protocol::write_error_response(
+                        &mut writer,
+                        "28P01",
+                        "authentication failed",
+                        None,
+                    )
+                    .await
+                    .ok();
+                    return;
+                }
+
+                pg_protocol::write_auth_ok(&mut writer).await.ok();
+                pg_protocol::write_ready_for_query(&mut writer, b'I')
+                    .await
+                    .ok();
+
+                handle_client(reader, writer, engine).await;
             }
-            username = username.trim().to_string();
-
-            writer.write_all(b"Password: ").await.ok();
-            writer.flush().await.ok();
-
-            let mut password = String::new();
-            if reader.read_line(&mut password).await.is_err() {
-                return;
-            }
-            password = password.trim().to_string();
-
-            let authenticated = auth.lock().await.verify(&username, &password);
-
-            if !authenticated {
-                writer.write_all(b"Authentication failed\n").await.ok();
-                return;
-            }
-
-            writer.write_all(b"Connected to Poubelle DB\n").await.ok();
-
-            handle_client(reader, writer, engine).await;
-        });
+            .instrument(connection_span),
+        );
     }
 }
@@ -0,0 +1,285 @@
+//! The real PostgreSQL frontend/backend wire protocol (v3), simple query
+//! subset only: StartupMessage, cleartext password auth, `Query` / simple
+//! results. This is what lets `psql`, libpq, and other postgres drivers
+//! connect to Poubelle directly, instead of only our own SDK.
+//!
+//! Every tagged message is a one-byte tag, a 4-byte big-endian length
+//! (covering the length field itself but not the tag byte), and a
+//! tag-specific payload — see the [Postgres protocol docs][pg] for the
+//! authoritative layout this mirrors.
+//!
+//! [pg]: https://www.postgresql.org/docs/current/protocol-message-formats.html
+
+use std::collections::HashMap;
+use std::io;
+use storage::Value;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Sent as the untagged StartupMessage's second Int32, identifying
+/// protocol version 3.0 (major 3, minor 0).
+const PROTOCOL_VERSION_3: i32 = 196608;
+
+/// Sent instead of a protocol version when a client wants to negotiate SSL
+/// before the real startup message.
+const SSL_REQUEST_CODE: i32 = 80877103;
+
+/// The OID Poubelle reports for every column, since every value is sent in
+/// text format regardless of its `storage::Value` variant. `25` is
+/// Postgres's own `text` type OID, so clients that don't special-case it
+/// just treat every column as a string.
+const TEXT_OID: i32 = 25;
+
+pub struct StartupParams {
+    pub user: String,
+    pub database: String,
+}
+
+/// One frontend message in the simple query cycle.
+pub enum Frontend {
+    Query(String),
+    /// The client is closing the connection cleanly (`Terminate`).
+    Terminate,
+}
+
+/// Read the untagged StartupMessage, transparently handling an `SSLRequest`
+/// first by replying `'N'` (SSL not supported) so `psql`/libpq — which
+/// request SSL by default — fall back to plaintext instead of hanging.
+/// Real TLS support is a separate concern.
+pub async fn read_startup_message<R, W>(reader: &mut R, writer: &mut W) -> io::Result<StartupParams>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    loop {
+        let len = reader.read_u32().await?;
+        let code = reader.read_i32().await?;
+
+        if code == SSL_REQUEST_CODE {
+            writer.write_u8(b'N').await?;
+            writer.flush().await?;
+            continue;
+        }
+
+        let _version = code.max(PROTOCOL_VERSION_3); // accept any 3.x minor
+        let params_len = (len as usize).saturating_sub(8);
+        let mut buf = vec![0u8; params_len];
+        reader.read_exact(&mut buf).await?;
+
+        let params = parse_cstring_pairs(&buf);
+        let user = params.get("user").cloned().unwrap_or_default();
+        let database = params
+            .get("database")
+            .cloned()
+            .unwrap_or_else(|| user.clone());
+        return Ok(StartupParams { user, database });
+    }
+}
+
+fn parse_cstring_pairs(buf: &[u8]) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    let mut parts = buf.split(|&b| b == 0);
+    loop {
+        let key = match parts.next() {
+            Some(k) if !k.is_empty() => k,
+            _ => break,
+        };
+        let value = parts.next().unwrap_or(&[]);
+        map.insert(
+            String::from_utf8_lossy(key).into_owned(),
+            String::from_utf8_lossy(value).into_owned(),
+        );
+    }
+    map
+}
+
+fn cstr(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+async fn write_message<W, F>(writer: &mut W, tag: u8, body: F) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+    F: FnOnce(&mut Vec<u8>),
+{
+    let mut payload = Vec::new();
+    body(&mut payload);
+    let len = (payload.len() + 4) as u32;
+    writer.write_u8(tag).await?;
+    writer.write_u32(len).await?;
+    writer.write_all(&payload).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+async fn read_message<R: AsyncRead + Unpin>(reader: &mut R) -> io::Result<(u8, Vec<u8>)> {
+    let tag = reader.read_u8().await?;
+    let len = reader.read_u32().await?;
+    let body_len = (len as usize).saturating_sub(4);
+    let mut payload = vec![0u8; body_len];
+    reader.read_exact(&mut payload).await?;
+    Ok((tag, payload))
+}
+
+/// `AuthenticationCleartextPassword`.
+pub async fn write_auth_cleartext<W: AsyncWrite + Unpin>(writer: &mut W) -> io::Result<()> {
+    write_message(writer, b'R', |buf| {
+        buf.extend_from_slice(&3i32.to_be_bytes())
+    })
+    .await
+}
+
+/// `AuthenticationOk`.
+pub async fn write_auth_ok<W: AsyncWrite + Unpin>(writer: &mut W) -> io::Result<()> {
+    write_message(writer, b'R', |buf| {
+        buf.extend_from_slice(&0i32.to_be_bytes())
+    })
+    .await
+}
+
+/// Read a `PasswordMessage`.
+pub async fn read_password_message<R: AsyncRead + Unpin>(reader: &mut R) -> io::Result<String> {
+    let (tag, payload) = read_message(reader).await?;
+    if tag != b'p' {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("expected PasswordMessage, got {:#04x}", tag),
+        ));
+    }
+    Ok(cstr(&payload))
+}
+
+/// Read the next simple-query-cycle frontend message (`Query` or
+/// `Terminate`).
+pub async fn read_frontend_message<R: AsyncRead + Unpin>(reader: &mut R) -> io::Result<Frontend> {
+    let (tag, payload) = read_message(reader).await?;
+    match tag {
+        b'Q' => Ok(Frontend::Query(cstr(&payload))),
+        b'X' => Ok(Frontend::Terminate),
+        other => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unexpected frontend message {:#04x}", other),
+        )),
+    }
+}
+
+/// `ReadyForQuery`. `status` is the backend transaction status byte: `I`
+/// idle, `T` in a transaction, `E` in a failed transaction.
+pub async fn write_ready_for_query<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    status: u8,
+) -> io::Result<()> {
+    write_message(writer, b'Z', |buf| buf.push(status)).await
+}
+
+/// `RowDescription`. Every column is reported as Postgres's `text` type,
+/// since [`write_data_row`] always encodes values in text format.
+pub async fn write_row_description<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    columns: &[String],
+) -> io::Result<()> {
+    write_message(writer, b'T', |buf| {
+        buf.extend_from_slice(&(columns.len() as i16).to_be_bytes());
+        for name in columns {
+            buf.extend_from_slice(name.as_bytes());
+            buf.push(0);
+            buf.extend_from_slice(&0i32.to_be_bytes()); // table OID
+            buf.extend_from_slice(&0i16.to_be_bytes()); // column attribute number
+            buf.extend_from_slice(&TEXT_OID.to_be_bytes());
+            buf.extend_from_slice(&(-1i16).to_be_bytes()); // type length (variable)
+            buf.extend_from_slice(&(-1i32).to_be_bytes()); // type modifier
+            buf.extend_from_slice(&0i16.to_be_bytes()); // format code: text
+        }
+    })
+    .await
+}
+
+/// `DataRow`, with each column text-encoded (`None` for SQL `NULL`).
+pub async fn write_data_row<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    values: &[Option<String>],
+) -> io::Result<()> {
+    write_message(writer, b'D', |buf| {
+        buf.extend_from_slice(&(values.len() as i16).to_be_bytes());
+        for value in values {
+            match value {
+                Some(s) => {
+                    buf.extend_from_slice(&(s.len() as i32).to_be_bytes());
+                    buf.extend_from_slice(s.as_bytes());
+                }
+                None => buf.extend_from_slice(&(-1i32).to_be_bytes()),
+            }
+        }
+    })
+    .await
+}
+
+/// Render a [`Value`] the way [`write_data_row`] encodes it: `None` for
+/// `NULL`, the plain text form otherwise.
+pub fn value_to_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::Int(n) => Some(n.to_string()),
+        Value::Float(f) => Some(f.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Text(s) => Some(s.clone()),
+        Value::Timestamp(ts) => Some(ts.to_string()),
+    }
+}
+
+/// `CommandComplete`, e.g. `"SELECT 3"` or `"BEGIN"`.
+pub async fn write_command_complete<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    tag: &str,
+) -> io::Result<()> {
+    write_message(writer, b'C', |buf| {
+        buf.extend_from_slice(tag.as_bytes());
+        buf.push(0);
+    })
+    .await
+}
+
+/// `ErrorResponse`. `redirect` is Poubelle-specific (no real SQLSTATE
+/// covers "retry against another node"), so it's carried in the `Detail`
+/// field rather than invented as a fake severity/code.
+pub async fn write_error_response<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    sqlstate: &str,
+    message: &str,
+    redirect: Option<u64>,
+) -> io::Result<()> {
+    write_message(writer, b'E', |buf| {
+        buf.push(b'S');
+        buf.extend_from_slice(b"ERROR");
+        buf.push(0);
+        buf.push(b'C');
+        buf.extend_from_slice(sqlstate.as_bytes());
+        buf.push(0);
+        buf.push(b'M');
+        buf.extend_from_slice(message.as_bytes());
+        buf.push(0);
+        if let Some(node) = redirect {
+            buf.push(b'D');
+            buf.extend_from_slice(format!("redirect to node {}", node).as_bytes());
+            buf.push(0);
+        }
+        buf.push(0); // field list terminator
+    })
+    .await
+}
+
+/// `NotificationResponse`, Postgres's `LISTEN`/`NOTIFY` delivery message.
+pub async fn write_notification<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    channel: &str,
+    payload: &str,
+) -> io::Result<()> {
+    write_message(writer, b'A', |buf| {
+        buf.extend_from_slice(&0i32.to_be_bytes()); // backend PID, unused
+        buf.extend_from_slice(channel.as_bytes());
+        buf.push(0);
+        buf.extend_from_slice(payload.as_bytes());
+        buf.push(0);
+    })
+    .await
+}
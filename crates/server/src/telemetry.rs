@@ -0,0 +1,50 @@
+//! Structured tracing for connections, auth, and query execution.
+//!
+//! Exports spans to an OTLP collector when `POUBELLE_OTLP_ENDPOINT` is set;
+//! otherwise logs to stderr through a plain `fmt` subscriber, so existing
+//! console output is unaffected when tracing isn't configured.
+
+use std::env;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Registry};
+
+/// Install the global tracing subscriber. Call once at startup, before any
+/// spans are created.
+pub fn init() -> anyhow::Result<()> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let fmt_layer = tracing_subscriber::fmt::layer().with_writer(std::io::stderr);
+
+    match env::var("POUBELLE_OTLP_ENDPOINT") {
+        Ok(endpoint) => {
+            let exporter = opentelemetry_otlp::SpanExporter::builder()
+                .with_tonic()
+                .with_endpoint(endpoint)
+                .build()?;
+
+            let provider = opentelemetry_sdk::trace::TracerProvider::builder()
+                .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+                .with_resource(opentelemetry_sdk::Resource::new(vec![
+                    opentelemetry::KeyValue::new("service.name", "poubelle"),
+                ]))
+                .build();
+
+            let tracer = opentelemetry::trace::TracerProvider::tracer(&provider, "poubelle");
+            opentelemetry::global::set_tracer_provider(provider);
+
+            Registry::default()
+                .with(filter)
+                .with(tracing_opentelemetry::layer().with_tracer(tracer))
+                .with(fmt_layer)
+                .try_init()?;
+        }
+        Err(_) => {
+            Registry::default()
+                .with(filter)
+                .with(fmt_layer)
+                .try_init()?;
+        }
+    }
+
+    Ok(())
+}
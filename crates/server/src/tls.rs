@@ -0,0 +1,61 @@
+//! Optional TLS for the TCP (postgres wire protocol) and HTTP listeners,
+//! gated on `POUBELLE_TLS_CERT`/`POUBELLE_TLS_KEY` (PEM paths) both being
+//! set. When either is unset, both listeners fall back to plaintext so
+//! local dev is unaffected.
+
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::Arc;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+
+fn cert_and_key_paths() -> Option<(String, String)> {
+    let cert = env::var("POUBELLE_TLS_CERT").ok()?;
+    let key = env::var("POUBELLE_TLS_KEY").ok()?;
+    Some((cert, key))
+}
+
+/// Build a [`TlsAcceptor`] for the raw TCP listener from
+/// `POUBELLE_TLS_CERT`/`POUBELLE_TLS_KEY`, or `None` if either is unset.
+pub fn acceptor_from_env() -> anyhow::Result<Option<TlsAcceptor>> {
+    let Some((cert_path, key_path)) = cert_and_key_paths() else {
+        return Ok(None);
+    };
+
+    let cert_chain = load_certs(&cert_path)?;
+    let private_key = load_private_key(&key_path)?;
+
+    let config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(cert_chain, private_key)?;
+
+    Ok(Some(TlsAcceptor::from(Arc::new(config))))
+}
+
+/// Build the equivalent `axum_server` rustls config for the HTTP listener
+/// from the same env vars, so both listeners agree on whether TLS is on
+/// and which cert/key back it.
+pub async fn rustls_config_from_env(
+) -> anyhow::Result<Option<axum_server::tls_rustls::RustlsConfig>> {
+    let Some((cert_path, key_path)) = cert_and_key_paths() else {
+        return Ok(None);
+    };
+
+    let config = axum_server::tls_rustls::RustlsConfig::from_pem_file(cert_path, key_path).await?;
+    Ok(Some(config))
+}
+
+fn load_certs(path: &str) -> anyhow::Result<Vec<CertificateDer<'static>>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    rustls_pemfile::certs(&mut reader)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Into::into)
+}
+
+fn load_private_key(path: &str) -> anyhow::Result<PrivateKeyDer<'static>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    rustls_pemfile::private_key(&mut reader)?
+        .ok_or_else(|| anyhow::anyhow!("no private key found in {}", path))
+}
@@ -0,0 +1,199 @@
+//! Pluggable backend for where a [`crate::Storage`]'s catalog and table
+//! pages actually live.
+//!
+//! `Storage` only ever talks to a `dyn StorageBackend`, so the page/catalog
+//! layout (fixed-size pages, a bincode-serialized catalog) stays identical
+//! whether the bytes end up on local disk, in a plain `DashMap` (tests,
+//! ephemeral use), or as objects in S3 — see [`crate::backup::S3Backend`]
+//! behind the `s3-backup` feature. The write-ahead log is the one exception:
+//! it's always local, since it exists to survive a crash before a backend
+//! round-trip has happened at all.
+
+use crate::storage::StorageError;
+use async_trait::async_trait;
+use bytes::Bytes;
+use dashmap::DashMap;
+use std::path::PathBuf;
+
+/// Where a [`crate::Storage`]'s blobs (the catalog, each table's pages) are
+/// persisted. `key` is an opaque, backend-chosen path-like string, e.g.
+/// `"catalog.bin"` or `"{table}/{page_id}"`.
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// Fetch the bytes stored at `key`, or [`StorageError::BlobNotFound`] if
+    /// nothing has been put there yet.
+    async fn blob_fetch(&self, key: &str) -> Result<Bytes, StorageError>;
+
+    /// Store `bytes` at `key`, overwriting whatever was there before.
+    async fn blob_put(&self, key: &str, bytes: Bytes) -> Result<(), StorageError>;
+
+    /// List every key starting with `prefix`.
+    async fn blob_list(&self, prefix: &str) -> Result<Vec<String>, StorageError>;
+
+    /// Delete the blob at `key`. Deleting a key that doesn't exist is not
+    /// an error.
+    async fn blob_delete(&self, key: &str) -> Result<(), StorageError>;
+}
+
+/// Stores each blob as a file on local disk, rooted at a directory. The
+/// backend Poubelle has always used.
+pub struct LocalFileBackend {
+    root: PathBuf,
+}
+
+impl LocalFileBackend {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path_for(&self, key: &str) -> PathBuf {
+        self.root.join(key)
+    }
+}
+
+#[async_trait]
+impl StorageBackend for LocalFileBackend {
+    async fn blob_fetch(&self, key: &str) -> Result<Bytes, StorageError> {
+        match tokio::fs::read(self.path_for(key)).await {
+            Ok(bytes) => Ok(Bytes::from(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(StorageError::BlobNotFound(key.to_string()))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn blob_put(&self, key: &str, bytes: Bytes) -> Result<(), StorageError> {
+        let path = self.path_for(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, &bytes).await?;
+        Ok(())
+    }
+
+    /// Lists the files directly inside the directory `prefix` names, so
+    /// `prefix` must be a whole key directory such as `"t/"`; keys in
+    /// nested directories are not listed.
+    async fn blob_list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        let dir = self.path_for(prefix.trim_end_matches('/'));
+        let mut keys = Vec::new();
+
+        let mut entries = match tokio::fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(keys),
+            Err(e) => return Err(e.into()),
+        };
+
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_file() {
+                if let Some(name) = entry.file_name().to_str() {
+                    keys.push(format!("{}{}", prefix, name));
+                }
+            }
+        }
+
+        Ok(keys)
+    }
+
+    async fn blob_delete(&self, key: &str) -> Result<(), StorageError> {
+        match tokio::fs::remove_file(self.path_for(key)).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Pure in-memory backend — nothing touches disk. Meant for tests and
+/// short-lived/ephemeral instances.
+#[derive(Default)]
+pub struct MemoryBackend {
+    blobs: DashMap<String, Vec<u8>>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl StorageBackend for MemoryBackend {
+    async fn blob_fetch(&self, key: &str) -> Result<Bytes, StorageError> {
+        self.blobs
+            .get(key)
+            .map(|bytes| Bytes::from(bytes.clone()))
+            .ok_or_else(|| StorageError::BlobNotFound(key.to_string()))
+    }
+
+    async fn blob_put(&self, key: &str, bytes: Bytes) -> Result<(), StorageError> {
+        self.blobs.insert(key.to_string(), bytes.to_vec());
+        Ok(())
+    }
+
+    async fn blob_list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        Ok(self
+            .blobs
+            .iter()
+            .map(|entry| entry.key().clone())
+            .filter(|key| key.starts_with(prefix))
+            .collect())
+    }
+
+    async fn blob_delete(&self, key: &str) -> Result<(), StorageError> {
+        self.blobs.remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn roundtrip(backend: &impl StorageBackend) {
+        assert!(matches!(
+            backend.blob_fetch("missing").await,
+            Err(StorageError::BlobNotFound(_))
+        ));
+
+        backend
+            .blob_put("t/0", Bytes::from_static(b"page zero"))
+            .await
+            .unwrap();
+        backend
+            .blob_put("t/1", Bytes::from_static(b"page one"))
+            .await
+            .unwrap();
+        backend
+            .blob_put("other/0", Bytes::from_static(b"unrelated"))
+            .await
+            .unwrap();
+
+        let fetched = backend.blob_fetch("t/0").await.unwrap();
+        assert_eq!(&fetched[..], b"page zero");
+
+        let mut listed = backend.blob_list("t/").await.unwrap();
+        listed.sort();
+        assert_eq!(listed, vec!["t/0".to_string(), "t/1".to_string()]);
+
+        backend.blob_delete("t/0").await.unwrap();
+        assert!(matches!(
+            backend.blob_fetch("t/0").await,
+            Err(StorageError::BlobNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn memory_backend_roundtrips() {
+        roundtrip(&MemoryBackend::new()).await;
+    }
+
+    #[tokio::test]
+    async fn local_file_backend_roundtrips() {
+        let dir =
+            std::env::temp_dir().join(format!("poubelle-backend-test-{}", std::process::id()));
+        roundtrip(&LocalFileBackend::new(&dir)).await;
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,12 +1,17 @@
+mod backend;
 mod page;
 mod storage;
 mod types;
+mod version;
+mod wal;
 
 #[cfg(feature = "s3-backup")]
 pub mod backup;
 
-pub use storage::{Storage, StorageError};
+pub use backend::{LocalFileBackend, MemoryBackend, StorageBackend};
+pub use storage::{Storage, StorageBuilder, StorageError};
 pub use types::{ColumnType, Row, Value};
+pub use version::{CausalEntry, Dot, Sibling, VersionContext, WriterId};
 
 #[cfg(feature = "s3-backup")]
-pub use backup::{BackupError, BackupManifest, S3Backup, S3BackupConfig};
+pub use backup::{BackupError, BackupManifest, RetentionPolicy, S3Backend, S3Backup, S3BackupConfig};
@@ -1,12 +1,28 @@
+use crate::backend::{LocalFileBackend, MemoryBackend, StorageBackend};
 use crate::page::{Page, PAGE_SIZE};
 use crate::types::{ColumnType, Row};
+use crate::version::{CausalEntry, VersionContext};
+use crate::wal::{Wal, WalRecord};
+use dashmap::mapref::entry::Entry;
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs::{File, OpenOptions};
-use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::PathBuf;
+use std::sync::Arc;
 use thiserror::Error;
+use tokio::sync::{broadcast, Mutex};
+
+/// Blob key the catalog is stored under, regardless of backend.
+const CATALOG_KEY: &str = "catalog.bin";
+
+/// Blob key the `username -> password hash` authentication table is stored
+/// under — a reserved system area alongside the catalog, not a SQL table.
+const AUTH_KEY: &str = "auth.bin";
+
+/// How many un-received row-insert events a table's change-feed buffers
+/// before a lagging subscriber starts missing them. Sized generously since
+/// each event is just a `Row`, not a page's worth of bytes.
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
 
 #[derive(Error, Debug)]
 pub enum StorageError {
@@ -18,91 +34,242 @@ pub enum StorageError {
     TableNotFound(String),
     #[error("Table already exists: {0}")]
     TableExists(String),
+    #[error("Blob not found: {0}")]
+    BlobNotFound(String),
+    #[error("Backend error: {0}")]
+    Backend(String),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TableMeta {
     pub name: String,
     pub columns: HashMap<String, ColumnType>,
     pub page_count: usize,
+    /// Rows written through [`Storage::insert_versioned_row`], keyed by the
+    /// caller-chosen row key. Absent from older catalogs, which never wrote
+    /// any — defaults to empty rather than failing deserialization.
+    #[serde(default)]
+    pub versions: HashMap<String, CausalEntry>,
 }
 
+/// On-disk shape of the catalog blob. Kept as a plain `HashMap` (rather
+/// than the in-memory `DashMap`) so the persisted format doesn't change
+/// just because the in-memory representation did.
 #[derive(Debug, Serialize, Deserialize)]
 struct Catalog {
     tables: HashMap<String, TableMeta>,
 }
 
+/// The blob key a table's page lives under in whatever [`StorageBackend`]
+/// is in use.
+fn page_key(table: &str, page_id: usize) -> String {
+    format!("{}/{}", table, page_id)
+}
+
 pub struct Storage {
+    /// Directory the write-ahead log lives in. The WAL is always local,
+    /// even when the catalog/pages are backed by something remote — it
+    /// exists to survive a crash before a backend round-trip happens at
+    /// all, so it wouldn't help to put it on the far end of a network call.
     path: PathBuf,
-    catalog: Catalog,
+    /// Per-table metadata, keyed by table name. A `DashMap` rather than one
+    /// `HashMap` behind a single lock, so two statements only ever contend
+    /// when they touch the *same* table: reads of table A and writes to
+    /// table B proceed fully in parallel, the same sharded-locking
+    /// guarantee `page_cache`/`event_channels` below already rely on. This
+    /// is what lets every mutating method here take `&self` instead of
+    /// `&mut self` — `Storage` is meant to be shared behind an `Arc`, not a
+    /// `Mutex`, across concurrent connections.
+    catalog: DashMap<String, TableMeta>,
     page_cache: DashMap<(String, usize), Page>,
+    /// The WAL is a single append-only file, so it keeps its own narrow
+    /// lock rather than joining the per-table scheme above — held only for
+    /// the instant it takes to append or checkpoint, never across an
+    /// `.await` that touches the catalog or a backend call.
+    wal: Mutex<Wal>,
+    backend: Arc<dyn StorageBackend>,
+    /// Per-table change-feed, lazily created on first `subscribe`. Rows are
+    /// published here after they're durably applied, so `LISTEN`ers never
+    /// see a row that a crash could still roll back.
+    event_channels: DashMap<String, broadcast::Sender<Row>>,
+    /// Per-table write lock, lazily created on first write. `catalog`'s
+    /// `DashMap` entry API only protects the metadata read/update it wraps
+    /// — it doesn't hold across the `load_page`/`save_page` round trip a
+    /// row insert also needs, so two concurrent inserts into the same
+    /// table could otherwise both load the same page, each append their
+    /// own row to their own copy, and have the later `save_page` silently
+    /// overwrite the other's. This lock is held across that whole
+    /// load-mutate-save sequence instead.
+    write_locks: DashMap<String, Arc<Mutex<()>>>,
 }
 
 impl Storage {
-    pub fn open(path: PathBuf) -> Result<Self, StorageError> {
-        let catalog_path = path.join("catalog.bin");
-
-        let catalog = if catalog_path.exists() {
-            let file = File::open(&catalog_path)?;
-            let reader = BufReader::new(file);
-            bincode::deserialize_from(reader)?
-        } else {
-            std::fs::create_dir_all(&path)?;
-            Catalog {
-                tables: HashMap::new(),
+    /// Open (or create) storage at `path` on local disk — the backend
+    /// Poubelle has always used. Equivalent to
+    /// `StorageBuilder::local(path).open().await`.
+    pub async fn open(path: PathBuf) -> Result<Self, StorageError> {
+        StorageBuilder::local(path).open().await
+    }
+
+    async fn open_with_backend(
+        path: PathBuf,
+        backend: Arc<dyn StorageBackend>,
+    ) -> Result<Self, StorageError> {
+        std::fs::create_dir_all(&path)?;
+
+        let catalog: DashMap<String, TableMeta> = match backend.blob_fetch(CATALOG_KEY).await {
+            Ok(bytes) => {
+                let catalog: Catalog = bincode::deserialize(&bytes)?;
+                catalog.tables.into_iter().collect()
             }
+            Err(StorageError::BlobNotFound(_)) => DashMap::new(),
+            Err(e) => return Err(e),
         };
 
-        Ok(Self {
+        let pending = Wal::replay(&path)?;
+        // Continue sequence numbering from one past the highest `seq` this
+        // log saw, so reopening a WAL that wasn't checkpointed before a
+        // restart never reassigns a sequence number already on disk.
+        let next_seq = pending.iter().map(|entry| entry.seq + 1).max().unwrap_or(0);
+        let wal = Wal::open(&path, next_seq)?;
+
+        let storage = Self {
             path,
             catalog,
             page_cache: DashMap::new(),
-        })
-    }
-
-    fn save_catalog(&self) -> Result<(), StorageError> {
-        let catalog_path = self.path.join("catalog.bin");
-        let file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(catalog_path)?;
-        let writer = BufWriter::new(file);
-        bincode::serialize_into(writer, &self.catalog)?;
+            wal: Mutex::new(wal),
+            backend,
+            event_channels: DashMap::new(),
+            write_locks: DashMap::new(),
+        };
+
+        // Re-apply whatever the WAL saw that the catalog/pages may not
+        // reflect yet, e.g. because of an unclean shutdown. Records that no
+        // longer apply (a table already created, a row already on disk)
+        // are expected and ignored rather than treated as recovery failure.
+        for entry in pending {
+            let _ = storage.apply_record(entry.record).await;
+        }
+        storage.wal.lock().await.checkpoint()?;
+
+        Ok(storage)
+    }
+
+    /// Apply an already-logged record to the catalog/pages, without
+    /// appending it to the WAL again. Used both by the public mutating
+    /// methods (after they've logged the record) and by crash recovery.
+    async fn apply_record(&self, record: WalRecord) -> Result<(), StorageError> {
+        match record {
+            WalRecord::CreateTable { name, columns } => {
+                self.apply_create_table(name, columns).await
+            }
+            WalRecord::DropTable { name } => self.apply_drop_table(&name).await,
+            WalRecord::InsertRow { table, row } => self.apply_insert_row(&table, row).await,
+            WalRecord::InsertVersionedRow {
+                table,
+                key,
+                writer,
+                row,
+                client_context,
+            } => self
+                .apply_insert_versioned_row(&table, &key, &writer, row, &client_context)
+                .await
+                .map(|_| ()),
+        }
+    }
+
+    /// The lock a write to `table` must hold across its load-mutate-save
+    /// sequence, creating it on first use. Returned as an owned `Arc`
+    /// rather than a `DashMap` guard so the lock can be held across an
+    /// `.await` without also pinning the shard `catalog`/`write_locks`
+    /// live in.
+    fn table_write_lock(&self, table: &str) -> Arc<Mutex<()>> {
+        self.write_locks
+            .entry(table.to_string())
+            .or_insert_with(|| Arc::new(Mutex::new(())))
+            .clone()
+    }
+
+    /// Checkpoint the WAL if enough records have accumulated since the last
+    /// one, so it doesn't grow without bound.
+    async fn maybe_checkpoint(&self) -> Result<(), StorageError> {
+        let mut wal = self.wal.lock().await;
+        if wal.needs_checkpoint() {
+            wal.checkpoint()?;
+        }
         Ok(())
     }
 
-    pub fn create_table(
-        &mut self,
+    pub(crate) async fn save_catalog(&self) -> Result<(), StorageError> {
+        let tables: HashMap<String, TableMeta> = self
+            .catalog
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        let bytes = bincode::serialize(&Catalog { tables })?;
+        self.backend.blob_put(CATALOG_KEY, bytes.into()).await
+    }
+
+    pub async fn create_table(
+        &self,
         name: String,
         columns: HashMap<String, ColumnType>,
     ) -> Result<(), StorageError> {
-        if self.catalog.tables.contains_key(&name) {
-            return Err(StorageError::TableExists(name));
-        }
-
-        let meta = TableMeta {
+        self.wal.lock().await.append(&WalRecord::CreateTable {
             name: name.clone(),
-            columns,
-            page_count: 0,
-        };
+            columns: columns.clone(),
+        })?;
+        self.apply_create_table(name, columns).await?;
+        self.maybe_checkpoint().await
+    }
 
-        self.catalog.tables.insert(name, meta);
-        self.save_catalog()?;
+    /// Insert `name`'s metadata if it isn't already present. Uses
+    /// `DashMap`'s entry API rather than a separate contains-check, so two
+    /// connections racing to create the same table can't both observe
+    /// "doesn't exist yet" and both insert.
+    async fn apply_create_table(
+        &self,
+        name: String,
+        columns: HashMap<String, ColumnType>,
+    ) -> Result<(), StorageError> {
+        let lock = self.table_write_lock(&name);
+        let _guard = lock.lock().await;
+
+        match self.catalog.entry(name.clone()) {
+            Entry::Occupied(_) => return Err(StorageError::TableExists(name)),
+            Entry::Vacant(entry) => {
+                entry.insert(TableMeta {
+                    name,
+                    columns,
+                    page_count: 0,
+                    versions: HashMap::new(),
+                });
+            }
+        }
+
+        self.save_catalog().await?;
         Ok(())
     }
 
-    pub fn drop_table(&mut self, name: &str) -> Result<(), StorageError> {
-        if !self.catalog.tables.contains_key(name) {
+    pub async fn drop_table(&self, name: &str) -> Result<(), StorageError> {
+        self.wal.lock().await.append(&WalRecord::DropTable {
+            name: name.to_string(),
+        })?;
+        self.apply_drop_table(name).await?;
+        self.maybe_checkpoint().await
+    }
+
+    async fn apply_drop_table(&self, name: &str) -> Result<(), StorageError> {
+        let lock = self.table_write_lock(name);
+        let _guard = lock.lock().await;
+
+        if self.catalog.remove(name).is_none() {
             return Err(StorageError::TableNotFound(name.to_string()));
         }
+        self.save_catalog().await?;
 
-        self.catalog.tables.remove(name);
-        self.save_catalog()?;
-
-        let table_path = self.path.join(format!("{}.bin", name));
-        if table_path.exists() {
-            std::fs::remove_file(table_path)?;
+        for key in self.backend.blob_list(&format!("{}/", name)).await? {
+            self.backend.blob_delete(&key).await?;
         }
 
         self.page_cache.retain(|key, _| key.0 != name);
@@ -110,55 +277,162 @@ impl Storage {
         Ok(())
     }
 
-    pub fn insert_row(&mut self, table: &str, row: Row) -> Result<(), StorageError> {
-        let page_id = {
-            let meta = self
+    pub async fn insert_row(&self, table: &str, row: Row) -> Result<(), StorageError> {
+        self.wal.lock().await.append(&WalRecord::InsertRow {
+            table: table.to_string(),
+            row: row.clone(),
+        })?;
+        self.apply_insert_row(table, row).await?;
+        self.maybe_checkpoint().await
+    }
+
+    async fn apply_insert_row(&self, table: &str, row: Row) -> Result<(), StorageError> {
+        let lock = self.table_write_lock(table);
+        let _guard = lock.lock().await;
+
+        let (page_id, mut page_count_changed) = {
+            let mut meta = self
                 .catalog
-                .tables
                 .get_mut(table)
                 .ok_or_else(|| StorageError::TableNotFound(table.to_string()))?;
 
             if meta.page_count == 0 {
                 meta.page_count = 1;
-                0
+                (0, true)
             } else {
-                meta.page_count - 1
+                (meta.page_count - 1, false)
             }
         };
 
-        let table_path = self.path.join(format!("{}.bin", table));
         let mut page = self
-            .load_page(&table_path, page_id, table)
+            .load_page(page_id, table)
+            .await
             .unwrap_or_else(|_| Page::new());
 
-        page.add_row(row);
+        page.add_row(row.clone());
 
         let page_bytes = page.to_bytes()?;
         let needs_new_page = page_bytes.len() > PAGE_SIZE;
 
-        self.save_page(&table_path, page_id, &page, table)?;
+        self.save_page(page_id, &page, table).await?;
 
         if needs_new_page {
-            let meta = self.catalog.tables.get_mut(table).unwrap();
-            meta.page_count += 1;
+            if let Some(mut meta) = self.catalog.get_mut(table) {
+                meta.page_count += 1;
+            }
+            page_count_changed = true;
+        }
+
+        // The catalog blob only tracks `page_count` per table, and an
+        // insert that fits in the table's current last page doesn't change
+        // that — so there's nothing in it to persist. The WAL already made
+        // this insert durable; rewriting the whole catalog on every row
+        // regardless was exactly the per-insert cost the WAL was added to
+        // eliminate. Only write it back when `page_count` actually moved.
+        if page_count_changed {
+            self.save_catalog().await?;
+        }
+
+        if let Some(sender) = self.event_channels.get(table) {
+            // No subscribers is the common case and isn't an error.
+            let _ = sender.send(row);
         }
 
-        self.save_catalog()?;
         Ok(())
     }
 
-    pub fn scan_table(&self, table: &str) -> Result<Vec<Row>, StorageError> {
+    /// Subscribe to rows inserted into `table` from here on. Each call gets
+    /// its own receiver off a channel shared by all subscribers of that
+    /// table, created on first use.
+    pub fn subscribe(&self, table: &str) -> broadcast::Receiver<Row> {
+        self.event_channels
+            .entry(table.to_string())
+            .or_insert_with(|| broadcast::channel(EVENT_CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Write `row` to `table` under `key` with causality tracking: `writer`
+    /// identifies the caller, and `client_context` should be whatever
+    /// [`VersionContext`] it last read for this key (or
+    /// `VersionContext::new()` for a blind write). Siblings the context
+    /// dominates are superseded; concurrent siblings are kept. Returns the
+    /// merged context to present on the next read or write of this key.
+    pub async fn insert_versioned_row(
+        &self,
+        table: &str,
+        key: &str,
+        writer: &str,
+        row: Row,
+        client_context: &VersionContext,
+    ) -> Result<VersionContext, StorageError> {
+        self.wal
+            .lock()
+            .await
+            .append(&WalRecord::InsertVersionedRow {
+                table: table.to_string(),
+                key: key.to_string(),
+                writer: writer.to_string(),
+                row: row.clone(),
+                client_context: client_context.clone(),
+            })?;
+        let context = self
+            .apply_insert_versioned_row(table, key, writer, row, client_context)
+            .await?;
+        self.maybe_checkpoint().await?;
+        Ok(context)
+    }
+
+    async fn apply_insert_versioned_row(
+        &self,
+        table: &str,
+        key: &str,
+        writer: &str,
+        row: Row,
+        client_context: &VersionContext,
+    ) -> Result<VersionContext, StorageError> {
+        let lock = self.table_write_lock(table);
+        let _guard = lock.lock().await;
+
+        let mut meta = self
+            .catalog
+            .get_mut(table)
+            .ok_or_else(|| StorageError::TableNotFound(table.to_string()))?;
+
+        let entry = meta.versions.entry(key.to_string()).or_default();
+        let context = entry.write(writer, row, client_context);
+        drop(meta);
+
+        self.save_catalog().await?;
+        Ok(context)
+    }
+
+    /// Read every current sibling value stored under `key` in `table`,
+    /// together with the merged context to present on the next write.
+    /// `None` if no write has ever been made to this key.
+    pub fn read_versioned_row(
+        &self,
+        table: &str,
+        key: &str,
+    ) -> Result<Option<CausalEntry>, StorageError> {
         let meta = self
             .catalog
-            .tables
             .get(table)
             .ok_or_else(|| StorageError::TableNotFound(table.to_string()))?;
 
-        let mut rows = Vec::with_capacity(meta.page_count * 10);
-        let table_path = self.path.join(format!("{}.bin", table));
+        Ok(meta.versions.get(key).cloned())
+    }
 
-        for page_id in 0..meta.page_count {
-            if let Ok(page) = self.load_page(&table_path, page_id, table) {
+    pub async fn scan_table(&self, table: &str) -> Result<Vec<Row>, StorageError> {
+        let page_count = self
+            .catalog
+            .get(table)
+            .ok_or_else(|| StorageError::TableNotFound(table.to_string()))?
+            .page_count;
+
+        let mut rows = Vec::with_capacity(page_count * 10);
+
+        for page_id in 0..page_count {
+            if let Ok(page) = self.load_page(page_id, table).await {
                 rows.extend(page.rows);
             }
         }
@@ -167,65 +441,43 @@ impl Storage {
     }
 
     pub fn list_tables(&self) -> Vec<String> {
-        self.catalog.tables.keys().cloned().collect()
+        self.catalog
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect()
     }
 
-    pub fn get_table_meta(&self, table: &str) -> Option<&TableMeta> {
-        self.catalog.tables.get(table)
+    /// A snapshot of `table`'s metadata at the time of the call — cloned
+    /// rather than a borrow, since a `DashMap` entry can't outlive the
+    /// guard that reads it.
+    pub fn get_table_meta(&self, table: &str) -> Option<TableMeta> {
+        self.catalog.get(table).map(|entry| entry.clone())
     }
 
-    fn load_page(
-        &self,
-        table_path: &PathBuf,
-        page_id: usize,
-        table: &str,
-    ) -> Result<Page, StorageError> {
+    async fn load_page(&self, page_id: usize, table: &str) -> Result<Page, StorageError> {
         let cache_key = (table.to_string(), page_id);
 
         if let Some(cached) = self.page_cache.get(&cache_key) {
             return Ok(cached.clone());
         }
 
-        let file = File::open(table_path)?;
-        let mut reader = BufReader::new(file);
-        let offset = page_id * PAGE_SIZE;
-        reader.seek(SeekFrom::Start(offset as u64))?;
-
-        let mut buffer = vec![0u8; PAGE_SIZE];
-        let bytes_read = reader.read(&mut buffer)?;
-        buffer.truncate(bytes_read);
-
-        if buffer.is_empty() {
-            return Ok(Page::new());
-        }
-
-        let page = Page::from_bytes(&buffer)?;
+        let bytes = self.backend.blob_fetch(&page_key(table, page_id)).await?;
+        let page = Page::from_bytes(&bytes)?;
         self.page_cache.insert(cache_key, page.clone());
 
         Ok(page)
     }
 
-    fn save_page(
+    async fn save_page(
         &self,
-        table_path: &PathBuf,
         page_id: usize,
         page: &Page,
         table: &str,
     ) -> Result<(), StorageError> {
-        let file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .read(true)
-            .open(table_path)?;
-        let mut writer = BufWriter::new(file);
-
-        let offset = page_id * PAGE_SIZE;
-        writer.seek(SeekFrom::Start(offset as u64))?;
-
-        let mut bytes = page.to_bytes()?;
-        bytes.resize(PAGE_SIZE, 0);
-        writer.write_all(&bytes)?;
-        writer.flush()?;
+        let bytes = page.to_bytes()?;
+        self.backend
+            .blob_put(&page_key(table, page_id), bytes.into())
+            .await?;
 
         let cache_key = (table.to_string(), page_id);
         self.page_cache.insert(cache_key, page.clone());
@@ -239,8 +491,89 @@ impl Storage {
     }
 
     /// Flush all cached data to disk
-    pub fn flush(&self) -> Result<(), StorageError> {
-        self.save_catalog()
+    pub async fn flush(&self) -> Result<(), StorageError> {
+        self.save_catalog().await
+    }
+
+    /// Load the persisted `username -> password hash` table from the
+    /// reserved [`AUTH_KEY`] blob, the same system-area pattern as the
+    /// catalog — not a SQL table, so it isn't visible to ordinary queries.
+    /// Returns an empty map if nothing has been saved yet.
+    pub async fn load_auth_users(&self) -> Result<HashMap<String, String>, StorageError> {
+        match self.backend.blob_fetch(AUTH_KEY).await {
+            Ok(bytes) => Ok(bincode::deserialize(&bytes)?),
+            Err(StorageError::BlobNotFound(_)) => Ok(HashMap::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Persist `users` to the reserved [`AUTH_KEY`] blob.
+    pub async fn save_auth_users(
+        &self,
+        users: &HashMap<String, String>,
+    ) -> Result<(), StorageError> {
+        let bytes = bincode::serialize(users)?;
+        self.backend.blob_put(AUTH_KEY, bytes.into()).await
+    }
+}
+
+/// Picks the [`StorageBackend`] a [`Storage`] runs against, so callers
+/// aren't stuck with local disk: `StorageBuilder::memory(path).open()` for
+/// tests, `StorageBuilder::s3(path, config).open()` (behind the
+/// `s3-backup` feature) to run fully against an object store.
+pub struct StorageBuilder {
+    path: PathBuf,
+    spec: BackendSpec,
+}
+
+enum BackendSpec {
+    Local,
+    Memory,
+    #[cfg(feature = "s3-backup")]
+    S3(crate::backup::S3BackupConfig),
+}
+
+impl StorageBuilder {
+    /// Store the catalog/pages as files under `path`, alongside the WAL.
+    pub fn local(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            spec: BackendSpec::Local,
+        }
+    }
+
+    /// Store the catalog/pages purely in memory. The WAL still lives under
+    /// `path` on local disk.
+    pub fn memory(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            spec: BackendSpec::Memory,
+        }
+    }
+
+    /// Store the catalog/pages as objects in S3, keyed `{table}/{page_id}`.
+    /// The WAL still lives under `path` on local disk.
+    #[cfg(feature = "s3-backup")]
+    pub fn s3(path: impl Into<PathBuf>, config: crate::backup::S3BackupConfig) -> Self {
+        Self {
+            path: path.into(),
+            spec: BackendSpec::S3(config),
+        }
+    }
+
+    pub async fn open(self) -> Result<Storage, StorageError> {
+        let backend: Arc<dyn StorageBackend> = match self.spec {
+            BackendSpec::Local => Arc::new(LocalFileBackend::new(self.path.clone())),
+            BackendSpec::Memory => Arc::new(MemoryBackend::new()),
+            #[cfg(feature = "s3-backup")]
+            BackendSpec::S3(config) => Arc::new(
+                crate::backup::S3Backend::new(config)
+                    .await
+                    .map_err(|e| StorageError::Backend(e.to_string()))?,
+            ),
+        };
+
+        Storage::open_with_backend(self.path, backend).await
     }
 }
 
@@ -254,7 +587,7 @@ impl Storage {
     /// ```ignore
     /// use storage::{Storage, S3BackupConfig};
     ///
-    /// let storage = Storage::open("./data".into())?;
+    /// let storage = Storage::open("./data".into()).await?;
     /// let config = S3BackupConfig::new("my-bucket", "backups/");
     /// let manifest = storage.backup_to_s3(config).await?;
     /// println!("Backup created: {}", manifest.id);
@@ -264,7 +597,7 @@ impl Storage {
         config: crate::backup::S3BackupConfig,
     ) -> Result<crate::backup::BackupManifest, crate::backup::BackupError> {
         // Ensure all data is flushed to disk before backup
-        self.save_catalog()?;
+        self.save_catalog().await?;
 
         let backup = crate::backup::S3Backup::new(config).await?;
         backup.backup_storage(&self.path).await
@@ -291,7 +624,7 @@ impl Storage {
     ) -> Result<Self, crate::backup::BackupError> {
         let backup = crate::backup::S3Backup::new(config).await?;
         backup.restore_storage(backup_id, &restore_path).await?;
-        Ok(Self::open(restore_path)?)
+        Ok(Self::open(restore_path).await?)
     }
 
     /// List all available S3 backups
@@ -0,0 +1,112 @@
+//! Dotted version vectors for rows that may be written concurrently by more
+//! than one writer (e.g. a replicated or multi-writer S3 backend).
+//!
+//! Plain [`crate::Storage::insert_row`] is blind last-write-wins: whoever
+//! writes last clobbers whatever was there, which silently loses data once
+//! more than one writer can touch the same row. The types here let a caller
+//! opt into causality tracking instead: a write presents the context it last
+//! read, and a value is only discarded if that context proves the writer
+//! had already seen it. Concurrent writes neither side observed are kept
+//! side by side as siblings until a later write's context covers them all.
+
+use crate::types::Row;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Identifies whichever client/node is making a write, for causality
+/// tracking. Arbitrary and caller-supplied — poubelle doesn't assign these.
+pub type WriterId = String;
+
+/// A single causal event: writer `writer` advanced its own counter to
+/// `counter`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Dot {
+    pub writer: WriterId,
+    pub counter: u64,
+}
+
+/// The causal context a client last observed for a row — the merged dots of
+/// every sibling it read. Presented back on write so the server can tell
+/// whether the write supersedes what's stored or is concurrent with it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VersionContext {
+    counters: HashMap<WriterId, u64>,
+}
+
+impl VersionContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether this context already causally includes `dot` — i.e. whether
+    /// a write presenting this context has already seen the value `dot`
+    /// tags, so that value can be safely discarded in favor of the new one.
+    pub fn dominates(&self, dot: &Dot) -> bool {
+        self.counters
+            .get(&dot.writer)
+            .is_some_and(|&counter| counter >= dot.counter)
+    }
+
+    /// Fold `other`'s knowledge into this context.
+    pub fn merge(&mut self, other: &VersionContext) {
+        for (writer, &counter) in &other.counters {
+            let entry = self.counters.entry(writer.clone()).or_insert(0);
+            *entry = (*entry).max(counter);
+        }
+    }
+
+    /// Advance `writer`'s counter and return the dot for the value about to
+    /// be written under it.
+    fn next_dot(&mut self, writer: &str) -> Dot {
+        let counter = self.counters.entry(writer.to_string()).or_insert(0);
+        *counter += 1;
+        Dot {
+            writer: writer.to_string(),
+            counter: *counter,
+        }
+    }
+}
+
+/// One causally-tagged value. A quiescent row keeps exactly one; a
+/// concurrent write that neither side's context dominates leaves more than
+/// one until a later write's context covers them all.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sibling {
+    pub dot: Dot,
+    pub row: Row,
+}
+
+/// Everything poubelle remembers about one versioned row: its current
+/// sibling values, and the merged context a client should present on its
+/// next read or write of the row to collapse them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CausalEntry {
+    pub siblings: Vec<Sibling>,
+    pub context: VersionContext,
+}
+
+impl CausalEntry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a write made by `writer`, presenting `client_context` as the
+    /// context it last read for this row. Siblings `client_context`
+    /// dominates are superseded by `row`; siblings it doesn't dominate are
+    /// concurrent and survive alongside it. Returns the merged context to
+    /// hand back to the caller.
+    pub fn write(
+        &mut self,
+        writer: &str,
+        row: Row,
+        client_context: &VersionContext,
+    ) -> VersionContext {
+        self.siblings.retain(|s| !client_context.dominates(&s.dot));
+
+        self.context.merge(client_context);
+        let dot = self.context.next_dot(writer);
+        self.siblings.push(Sibling { dot, row });
+
+        self.context.clone()
+    }
+}